@@ -1,10 +1,15 @@
 use ark_ff::PrimeField;
-use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
+    Radix2EvaluationDomain,
+};
 use ark_std::rand::Rng;
-use merkle_tree::MerkleTree;
 use merlin::Transcript;
 
-use crate::{proof::FriProof, transcripts::GlobalTranscript, utils::test_rng_helper_from_scalar};
+use crate::{
+    merkle::MerkleTree, proof::FriProof, transcripts::GlobalTranscript,
+    utils::test_rng_helper_from_scalar,
+};
 
 pub struct FRI<F: PrimeField> {
     pub offset: F,
@@ -88,11 +93,10 @@ impl<F: PrimeField> FRI<F> {
             let codeword_length = codeword.len();
             assert!(omega.pow(&[(codeword_length - 1) as u64]) == omega.inverse().unwrap());
 
-            let db = MerkleTree::<F>::from_vec(codeword.to_vec());
-            let root = db.root_hash().unwrap();
-            proof.push_root(root.to_string());
+            let root = MerkleTree::new(&codeword).root();
+            proof.push_root(root);
 
-            transcript.append_message(b"root", root.as_bytes());
+            transcript.append_message(b"root", &root);
 
             codewords.push(codeword.clone());
 
@@ -169,6 +173,8 @@ impl<F: PrimeField> FRI<F> {
 
         let mut colinearity_tests = vec![];
         let mut merkle_auth_paths = vec![];
+        let current_codeword_db = MerkleTree::new(current_codeword);
+        let next_codeword_db = MerkleTree::new(next_codeword);
 
         for i in 0..self.num_colinearity_tests {
             // 1. colinearity tests
@@ -180,18 +186,10 @@ impl<F: PrimeField> FRI<F> {
             colinearity_tests.push(colinearity_test);
 
             // 2. merkle authentication paths
-            let current_codeword_db = MerkleTree::<F>::from_vec(current_codeword.to_vec());
-            let current_codeword_path1 =
-                current_codeword_db.get_proof(current_codeword[first_indexs[i]]);
-            let current_codeword_path2 =
-                current_codeword_db.get_proof(current_codeword[second_indexs[i]]);
-
-            let next_codeword_db = MerkleTree::<F>::from_vec(next_codeword.to_vec());
-            let next_codeword_path = next_codeword_db.get_proof(next_codeword[first_indexs[i]]);
             merkle_auth_paths.push((
-                current_codeword_path1,
-                current_codeword_path2,
-                next_codeword_path,
+                current_codeword_db.open(first_indexs[i]),
+                current_codeword_db.open(second_indexs[i]),
+                next_codeword_db.open(first_indexs[i]),
             ));
         }
 
@@ -200,46 +198,31 @@ impl<F: PrimeField> FRI<F> {
     }
 
     pub fn verify(&self, proof: &FriProof<F>) {
-        let last_codeword_db = MerkleTree::<F>::from_vec(proof.last_codeword.clone());
-        assert!(last_codeword_db.root_hash().unwrap() == proof.merkle_root.last().unwrap());
+        let last_codeword_length = proof.last_codeword.len();
+        assert!(last_codeword_length == self.codeword_length >> (self.num_rounds() - 1));
+        let last_codeword_db = MerkleTree::new(&proof.last_codeword);
+        assert!(last_codeword_db.root() == *proof.merkle_root.last().unwrap());
 
         let mut omega = self.omega;
         let mut offset = self.offset;
         let mut last_omega = omega;
-        let mut last_offset = offset;
         for _ in 0..self.num_rounds() - 1 {
             last_omega = last_omega.square();
-            last_offset = last_offset.square();
         }
-        let last_codeword_length = proof.last_codeword.len();
         assert!(
             last_omega.inverse().unwrap() == last_omega.pow(&[(last_codeword_length - 1) as u64])
         );
 
-        //    let last_domain  = Radix2EvaluationDomain::<T>::new(last_codeword_length).unwrap();
-        //    assert!(last_domain.group_gen == last_omega);
-        //    let last_poly_coefs = last_domain.coset_ifft(&proof.last_codeword);
-        //    let last_poly = DensePolynomial::from_coefficients_vec(last_poly_coefs).mul(last_offset.inverse().unwrap());
-        //    let  degree = last_codeword_length / self.expansion_factor - 1;
-        //    assert!(last_poly.degree() <= degree )
-
-        let mut alphas = vec![];
-        let mut transcript = self.init_transcript();
-        for r in 0..self.num_rounds() {
-            transcript.append_message(b"root", proof.merkle_root[r].as_bytes());
-
-            if r != self.num_rounds() - 1 {
-                let alpha: F = transcript.get_challenge(b"alpha");
-                alphas.push(alpha);
-            }
-        }
-        transcript.append_scalars(&proof.last_codeword);
+        // The last codeword evaluates p(X) on the coset offset * <ω>, so it evaluates
+        // p(offset * X) on <ω>, which has the degree of p.
+        let last_domain = Radix2EvaluationDomain::<F>::new(last_codeword_length).unwrap();
+        assert!(last_domain.group_gen == last_omega);
+        let last_poly =
+            DensePolynomial::from_coefficients_vec(last_domain.ifft(&proof.last_codeword));
+        let degree = last_codeword_length / self.expansion_factor - 1;
+        assert!(last_poly.degree() <= degree);
 
-        let top_indexs = self.sample_index(
-            self.codeword_length >> 1,
-            self.codeword_length >> (self.num_rounds() - 1),
-            &mut transcript,
-        );
+        let (alphas, top_indexs) = self.replay_transcript(proof);
 
         for r in 0..self.num_rounds() - 1 {
             let first_indexs = top_indexs
@@ -267,14 +250,45 @@ impl<F: PrimeField> FRI<F> {
 
             // 2. verify merkle path
             for i in 0..self.num_colinearity_tests {
+                let (y1, y2, y3) = &proof.colinearity_tests[r][i];
                 let (a, b, c) = &proof.merkle_auth_paths[r][i];
-                assert!(a.validate(&proof.merkle_root[r]));
-                assert!(b.validate(&proof.merkle_root[r]));
-                assert!(c.validate(&proof.merkle_root[r + 1]));
+                assert!(a.verify(&proof.merkle_root[r], first_indexs[i], y1));
+                assert!(b.verify(&proof.merkle_root[r], second_indexs[i], y2));
+                assert!(c.verify(&proof.merkle_root[r + 1], first_indexs[i], y3));
             }
         }
     }
 
+    /// The indexes queried in the first codeword, each one is tested together with the index
+    /// half of the codeword further.
+    pub fn query_indexes(&self, proof: &FriProof<F>) -> Vec<usize> {
+        self.replay_transcript(proof).1
+    }
+
+    /// Replays the transcript of the prover, returns the folding challenges and the indexes
+    /// queried in the first codeword.
+    fn replay_transcript(&self, proof: &FriProof<F>) -> (Vec<F>, Vec<usize>) {
+        let mut alphas = vec![];
+        let mut transcript = self.init_transcript();
+        for r in 0..self.num_rounds() {
+            transcript.append_message(b"root", &proof.merkle_root[r]);
+
+            if r != self.num_rounds() - 1 {
+                let alpha: F = transcript.get_challenge(b"alpha");
+                alphas.push(alpha);
+            }
+        }
+        transcript.append_scalars(&proof.last_codeword);
+
+        let top_indexs = self.sample_index(
+            self.codeword_length >> 1,
+            self.codeword_length >> (self.num_rounds() - 1),
+            &mut transcript,
+        );
+
+        (alphas, top_indexs)
+    }
+
     fn colinearity_test(a: (F, F), b: (F, F), c: (F, F)) {
         let x1_sub_x2 = a.0.sub(&b.0);
         let y1_sub_y2 = a.1.sub(&b.1);
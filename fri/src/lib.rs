@@ -1,4 +1,6 @@
 pub mod fri;
+pub mod lookup;
+pub mod merkle;
 pub mod proof;
 #[cfg(test)]
 pub mod tests;
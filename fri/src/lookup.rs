@@ -0,0 +1,349 @@
+//! Plookup over FRI, a lookup argument without trusted setup.
+//!
+//! f, h1, h2, z and the quotient q are committed with Merkle trees over their codewords on the
+//! coset of the FRI domain. The plookup identity is checked at a random ζ, and the claimed
+//! evaluations are bound to the codewords by the DEEP quotient
+//! c(X) = \sum λ^k * (p_k(X) - p_k(x_k)) / (X - x_k), whose degree is proven by `FRI::prove`.
+//! The verifier recomputes c(X) where FRI queries it from the openings of the codewords.
+//! The table is public, so the verifier evaluates t(X) itself.
+
+use ark_ff::{batch_inversion, PrimeField};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
+    Radix2EvaluationDomain,
+};
+use merlin::Transcript;
+use plookup::{
+    helpers::{compute_t_poly, compute_z_poly, evaluate_n_lagrange_poly},
+    table::SampleTable,
+};
+
+use crate::{
+    fri::FRI,
+    merkle::{Hash, MerklePath, MerkleTree},
+    proof::FriProof,
+    transcripts::GlobalTranscript,
+};
+
+/// The values of a codeword at a query index and at the index half of the codeword further,
+/// with their Merkle authentication paths.
+pub type QueryOpening<F> = (F, F, MerklePath, MerklePath);
+
+pub struct FriPlookUpProof<F: PrimeField> {
+    pub domain_size: usize,
+    /// Merkle roots of the codewords of f, h1, h2, z and q.
+    pub roots: Vec<Hash>,
+    /// Evaluations of f, h1, h2, z and q at their points of `opening_points`.
+    pub evaluations: Vec<Vec<F>>,
+    /// The openings of every codeword, for each query of `fri_proof`.
+    pub queries: Vec<Vec<QueryOpening<F>>>,
+    pub fri_proof: FriProof<F>,
+}
+
+pub struct FriPlookUp {
+    pub expansion_factor: usize,
+    pub num_colinearity_tests: usize,
+}
+
+/// ζ for f and q, ζ and ζω for h1, h2 and z, always in this order.
+fn opening_points<F: PrimeField>(zeta: F, zeta_omega: F) -> Vec<Vec<F>> {
+    vec![
+        vec![zeta],
+        vec![zeta, zeta_omega],
+        vec![zeta, zeta_omega],
+        vec![zeta, zeta_omega],
+        vec![zeta],
+    ]
+}
+
+/// Appends the Merkle root of `codeword` to the transcript.
+fn commit<F: PrimeField>(codeword: &[F], transcript: &mut Transcript) -> Hash {
+    let root = MerkleTree::new(codeword).root();
+    transcript.append_message(b"root", &root);
+    root
+}
+
+impl FriPlookUp {
+    pub fn new(expansion_factor: usize, num_colinearity_tests: usize) -> Self {
+        FriPlookUp {
+            expansion_factor,
+            num_colinearity_tests,
+        }
+    }
+
+    /// FRI over codewords of degree below 2|H|, the quotient has degree at most 2|H| - 2.
+    fn fri<F: PrimeField>(&self, domain_size: usize) -> FRI<F> {
+        let fri = FRI::new(
+            2 * domain_size * self.expansion_factor,
+            self.expansion_factor,
+            self.num_colinearity_tests,
+        );
+        assert!(
+            fri.num_rounds() > 1,
+            "Codewords should be long enough for FRI to query them"
+        );
+        fri
+    }
+
+    pub fn prove<F: PrimeField>(
+        &self,
+        f_table: &SampleTable<F>,
+        t_table: &SampleTable<F>,
+    ) -> FriPlookUpProof<F> {
+        let domain =
+            Radix2EvaluationDomain::<F>::new((f_table.size() + 1).max(t_table.size())).unwrap();
+        let size = domain.size();
+        let mut t_table = t_table.clone();
+        t_table.pad_to(size);
+        let mut f_table = f_table.clone();
+        let sorted_table = f_table.sort_by(&t_table);
+
+        let fri = self.fri::<F>(size);
+        let coset = Radix2EvaluationDomain::<F>::new(fri.codeword_length)
+            .unwrap()
+            .get_coset(fri.offset)
+            .unwrap();
+
+        let mut transcript = Transcript::new(b"fri plookup");
+        transcript.append_u64(b"size", size as u64);
+        transcript.append_scalars(&t_table.0);
+
+        // 1.Commit to the codewords of f and of the helper polynomials h1 and h2.
+        let interpolate =
+            |table: &SampleTable<F>| DensePolynomial::from_coefficients_vec(domain.ifft(&table.0));
+        let n = sorted_table.size() / 2;
+        let h1_table = SampleTable::from_scalar(sorted_table.0[..=n].to_vec());
+        let h2_table = SampleTable::from_scalar(sorted_table.0[n..].to_vec());
+        let t_poly = interpolate(&t_table);
+        let mut polys = vec![
+            interpolate(&f_table),
+            interpolate(&h1_table),
+            interpolate(&h2_table),
+        ];
+        let mut codewords = polys
+            .iter()
+            .map(|poly| coset.fft(poly.coeffs()))
+            .collect::<Vec<_>>();
+        let mut roots = codewords
+            .iter()
+            .map(|codeword| commit(codeword, &mut transcript))
+            .collect::<Vec<_>>();
+
+        // 2.Get challenge beta and gamma, commit to the codeword of z.
+        let beta: F = transcript.get_challenge(b"beta");
+        let gamma: F = transcript.get_challenge(b"gamma");
+        let z_poly = compute_z_poly(
+            n, &f_table, &t_table, &h1_table, &h2_table, &gamma, &beta, &domain,
+        );
+        codewords.push(coset.fft(z_poly.coeffs()));
+        roots.push(commit(&codewords[3], &mut transcript));
+        polys.push(z_poly);
+
+        // 3.Get challenge alpha, commit to the codeword of the quotient q.
+        let alpha: F = transcript.get_challenge(b"alpha");
+        let q_poly = compute_t_poly(
+            &polys[3], &polys[0], &t_poly, &polys[1], &polys[2], &domain, &beta, &gamma, &alpha,
+        );
+        codewords.push(coset.fft(q_poly.coeffs()));
+        roots.push(commit(&codewords[4], &mut transcript));
+        polys.push(q_poly);
+
+        // 4.Get challenge zeta, evaluate every polynomial at its opening points.
+        let zeta: F = transcript.get_challenge(b"zeta");
+        let points = opening_points(zeta, zeta.mul(domain.group_gen));
+        let evaluations = polys
+            .iter()
+            .zip(points.iter())
+            .map(|(poly, points)| points.iter().map(|x| poly.evaluate(x)).collect())
+            .collect::<Vec<Vec<F>>>();
+        for evals in evaluations.iter() {
+            transcript.append_scalars(evals);
+        }
+
+        // 5.Get challenge lambda, prove the degree of the DEEP quotient c(X) with FRI.
+        let lambda: F = transcript.get_challenge(b"lambda");
+        let elements = coset.elements().collect::<Vec<_>>();
+        let inverses = points[1]
+            .iter()
+            .map(|point| {
+                let mut inverse = elements.iter().map(|x| x.sub(point)).collect::<Vec<_>>();
+                batch_inversion(&mut inverse);
+                inverse
+            })
+            .collect::<Vec<_>>();
+        let mut deep_codeword = vec![F::zero(); fri.codeword_length];
+        let mut lambda_pow = F::one();
+        for (codeword, evals) in codewords.iter().zip(evaluations.iter()) {
+            // the points of a polynomial are a prefix of (ζ, ζω).
+            for (eval, inverse) in evals.iter().zip(inverses.iter()) {
+                for (c, (y, inv)) in deep_codeword
+                    .iter_mut()
+                    .zip(codeword.iter().zip(inverse.iter()))
+                {
+                    *c += lambda_pow.mul(y.sub(eval)).mul(inv);
+                }
+                lambda_pow.mul_assign(lambda);
+            }
+        }
+        let fri_proof = fri.prove(&deep_codeword);
+
+        // 6.Open the codewords where FRI queries c(X).
+        let half = fri.codeword_length / 2;
+        let trees = codewords
+            .iter()
+            .map(|codeword| MerkleTree::new(codeword))
+            .collect::<Vec<_>>();
+        let queries = fri
+            .query_indexes(&fri_proof)
+            .iter()
+            .map(|i| {
+                trees
+                    .iter()
+                    .zip(codewords.iter())
+                    .map(|(tree, codeword)| {
+                        (
+                            codeword[*i],
+                            codeword[i + half],
+                            tree.open(*i),
+                            tree.open(i + half),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        FriPlookUpProof {
+            domain_size: size,
+            roots,
+            evaluations,
+            queries,
+            fri_proof,
+        }
+    }
+
+    /// Panics if the proof does not show that f is a subset of `t_table`, as `FRI::verify` does.
+    pub fn verify<F: PrimeField>(&self, proof: &FriPlookUpProof<F>, t_table: &SampleTable<F>) {
+        let domain = Radix2EvaluationDomain::<F>::new(proof.domain_size).unwrap();
+        let size = domain.size();
+        assert_eq!(size, proof.domain_size);
+        assert!(t_table.size() <= size);
+        let mut t_table = t_table.clone();
+        t_table.pad_to(size);
+        let fri = self.fri::<F>(size);
+        assert_eq!(proof.roots.len(), 5);
+
+        let mut transcript = Transcript::new(b"fri plookup");
+        transcript.append_u64(b"size", size as u64);
+        transcript.append_scalars(&t_table.0);
+        for root in proof.roots[..3].iter() {
+            transcript.append_message(b"root", root);
+        }
+        let beta: F = transcript.get_challenge(b"beta");
+        let gamma: F = transcript.get_challenge(b"gamma");
+        transcript.append_message(b"root", &proof.roots[3]);
+        let alpha: F = transcript.get_challenge(b"alpha");
+        transcript.append_message(b"root", &proof.roots[4]);
+        let zeta: F = transcript.get_challenge(b"zeta");
+        let zeta_omega = zeta.mul(domain.group_gen);
+        let points = opening_points(zeta, zeta_omega);
+        assert_eq!(proof.evaluations.len(), points.len());
+        for (evals, points) in proof.evaluations.iter().zip(points.iter()) {
+            assert_eq!(evals.len(), points.len());
+            transcript.append_scalars(evals);
+        }
+        let lambda: F = transcript.get_challenge(b"lambda");
+
+        // 1.The plookup identity at ζ, t(ζ) and t(ζω) come from the table itself.
+        let evaluate_t = |x: F| {
+            domain
+                .evaluate_all_lagrange_coefficients(x)
+                .iter()
+                .zip(t_table.0.iter())
+                .map(|(l, t)| l.mul(t))
+                .sum::<F>()
+        };
+        let (t_eval_zeta, t_eval_zeta_omega) = (evaluate_t(zeta), evaluate_t(zeta_omega));
+        let evals = &proof.evaluations;
+        let f_eval_zeta = evals[0][0];
+        let (h1_eval_zeta, h1_eval_zeta_omega) = (evals[1][0], evals[1][1]);
+        let (h2_eval_zeta, h2_eval_zeta_omega) = (evals[2][0], evals[2][1]);
+        let (z_eval_zeta, z_eval_zeta_omega) = (evals[3][0], evals[3][1]);
+        let q_eval_zeta = evals[4][0];
+
+        let one = F::one();
+        let l1_eval_zeta = evaluate_n_lagrange_poly(&domain, 0, &zeta);
+        let ln_eval_zeta = evaluate_n_lagrange_poly(&domain, size - 1, &zeta);
+        let beta_plus_one = beta.add(one);
+        let gamma_mul_beta_plus_one = gamma.mul(beta_plus_one);
+
+        let term1 = l1_eval_zeta.add(ln_eval_zeta).mul(z_eval_zeta.sub(one));
+        let term2 = ln_eval_zeta.mul(h1_eval_zeta.sub(h2_eval_zeta_omega));
+        let term3 = zeta.sub(domain.element(size - 1)).mul(
+            z_eval_zeta
+                .mul(beta_plus_one)
+                .mul(gamma.add(f_eval_zeta))
+                .mul(
+                    gamma_mul_beta_plus_one
+                        .add(t_eval_zeta)
+                        .add(beta.mul(t_eval_zeta_omega)),
+                )
+                .sub(
+                    z_eval_zeta_omega
+                        .mul(
+                            gamma_mul_beta_plus_one
+                                .add(h1_eval_zeta)
+                                .add(beta.mul(h1_eval_zeta_omega)),
+                        )
+                        .mul(
+                            gamma_mul_beta_plus_one
+                                .add(h2_eval_zeta)
+                                .add(beta.mul(h2_eval_zeta_omega)),
+                        ),
+                ),
+        );
+        assert_eq!(
+            term1.add(alpha.mul(term2)).add(alpha.square().mul(term3)),
+            q_eval_zeta.mul(domain.evaluate_vanishing_polynomial(zeta)),
+        );
+
+        // 2.The DEEP quotient has low degree.
+        fri.verify(&proof.fri_proof);
+
+        // 3.The DEEP quotient FRI queries is the one of the committed codewords.
+        let deep_eval = |x: F, values: &[F]| {
+            let mut lambda_pow = F::one();
+            let mut eval = F::zero();
+            for ((y, evals), points) in values.iter().zip(evals.iter()).zip(points.iter()) {
+                for (v, point) in evals.iter().zip(points.iter()) {
+                    eval += lambda_pow
+                        .mul(y.sub(v))
+                        .mul(x.sub(point).inverse().unwrap());
+                    lambda_pow.mul_assign(lambda);
+                }
+            }
+            eval
+        };
+        let half = fri.codeword_length / 2;
+        let indexes = fri.query_indexes(&proof.fri_proof);
+        assert_eq!(proof.queries.len(), indexes.len());
+        for ((i, query), colinearity_test) in indexes
+            .iter()
+            .zip(proof.queries.iter())
+            .zip(proof.fri_proof.colinearity_tests[0].iter())
+        {
+            assert_eq!(query.len(), proof.roots.len());
+            // every value is bound to its root at its index.
+            for ((value1, value2, path1, path2), root) in query.iter().zip(proof.roots.iter()) {
+                assert!(path1.verify(root, *i, value1));
+                assert!(path2.verify(root, i + half, value2));
+            }
+
+            let x1 = fri.offset.mul(fri.omega.pow([*i as u64]));
+            let x2 = fri.offset.mul(fri.omega.pow([(i + half) as u64]));
+            let values1 = query.iter().map(|opening| opening.0).collect::<Vec<_>>();
+            let values2 = query.iter().map(|opening| opening.1).collect::<Vec<_>>();
+            assert_eq!(deep_eval(x1, &values1), colinearity_test.0);
+            assert_eq!(deep_eval(x2, &values2), colinearity_test.1);
+        }
+    }
+}
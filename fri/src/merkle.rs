@@ -0,0 +1,89 @@
+//! Merkle trees over codewords. An authentication path is checked against the index and the
+//! value of its leaf, so an opening binds both to the root.
+
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+fn hash_leaf<F: PrimeField>(leaf: &F) -> Hash {
+    let mut buf = vec![0u8];
+    leaf.serialize_uncompressed(&mut buf).unwrap();
+    Sha256::digest(&buf).into()
+}
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+pub struct MerkleTree {
+    /// layers[0] hashes the leaves, the last layer is the root.
+    layers: Vec<Vec<Hash>>,
+}
+
+/// The siblings of a leaf and of its ancestors, from the leaf up to the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerklePath(pub Vec<Hash>);
+
+impl MerkleTree {
+    pub fn new<F: PrimeField>(leaves: &[F]) -> Self {
+        assert!(
+            leaves.len().is_power_of_two(),
+            "Merkle tree should have a power of two leaves"
+        );
+
+        let mut layers = vec![leaves.iter().map(hash_leaf).collect::<Vec<_>>()];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hash_node(&pair[0], &pair[1]))
+                .collect();
+            layers.push(layer);
+        }
+
+        MerkleTree { layers }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// The authentication path of the leaf at `index`.
+    pub fn open(&self, index: usize) -> MerklePath {
+        let mut index = index;
+        let path = self.layers[..self.layers.len() - 1]
+            .iter()
+            .map(|layer| {
+                let sibling = layer[index ^ 1];
+                index /= 2;
+                sibling
+            })
+            .collect();
+
+        MerklePath(path)
+    }
+}
+
+impl MerklePath {
+    /// Whether `leaf` is the leaf at `index` of the tree with root `root`.
+    pub fn verify<F: PrimeField>(&self, root: &Hash, index: usize, leaf: &F) -> bool {
+        let mut index = index;
+        let mut hash = hash_leaf(leaf);
+        for sibling in self.0.iter() {
+            hash = if index & 1 == 0 {
+                hash_node(&hash, sibling)
+            } else {
+                hash_node(sibling, &hash)
+            };
+            index /= 2;
+        }
+
+        index == 0 && hash == *root
+    }
+}
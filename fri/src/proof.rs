@@ -1,18 +1,18 @@
-use std::fmt::Display;
-
 use ark_ff::PrimeField;
-use merkle_tree::Proof;
+
+use crate::merkle::{Hash, MerklePath};
 
 #[derive(Default, Debug)]
-pub struct FriProof<T: Display> {
-    pub merkle_root: Vec<String>,
+pub struct FriProof<T> {
+    pub merkle_root: Vec<Hash>,
     pub last_codeword: Vec<T>,
     pub colinearity_tests: Vec<Vec<(T, T, T)>>,
-    pub merkle_auth_paths: Vec<Vec<(Proof<T>, Proof<T>, Proof<T>)>>,
+    /// The paths of the two values of a colinearity test, and of the folded value.
+    pub merkle_auth_paths: Vec<Vec<(MerklePath, MerklePath, MerklePath)>>,
 }
 
 impl<T: PrimeField> FriProof<T> {
-    pub fn push_root(&mut self, root: String) {
+    pub fn push_root(&mut self, root: Hash) {
         self.merkle_root.push(root)
     }
 
@@ -22,7 +22,7 @@ impl<T: PrimeField> FriProof<T> {
 
     pub fn push_merkle_auth_paths(
         &mut self,
-        merkle_auth_path: Vec<(Proof<T>, Proof<T>, Proof<T>)>,
+        merkle_auth_path: Vec<(MerklePath, MerklePath, MerklePath)>,
     ) {
         self.merkle_auth_paths.push(merkle_auth_path)
     }
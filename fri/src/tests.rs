@@ -1,10 +1,11 @@
-use crate::fri::FRI;
+use crate::{fri::FRI, lookup::FriPlookUp, merkle::MerkleTree};
 use ark_bls12_381::Fr;
 use ark_ff::UniformRand;
 use ark_poly::{
     univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
 };
 use ark_std::test_rng;
+use plookup::table::SampleTable;
 
 #[test]
 fn test() {
@@ -32,3 +33,94 @@ fn test() {
 
     fri.verify(&proof);
 }
+
+#[test]
+#[should_panic]
+fn test_fri_rejects_high_degree() {
+    let expansion_factor = 4;
+    let codeword_length = 64 * expansion_factor;
+
+    // a random codeword has degree codeword_length - 1, the folds are still consistent.
+    let mut rng = test_rng();
+    let codeword = (0..codeword_length)
+        .map(|_| Fr::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let fri = FRI::<Fr>::new(codeword_length, expansion_factor, 4);
+    let proof = fri.prove(&codeword);
+    fri.verify(&proof);
+}
+
+#[test]
+fn test_merkle_path() {
+    let leaves = [0u64, 1, 2, 3, 4, 1, 6, 7].map(Fr::from);
+    let tree = MerkleTree::new(&leaves);
+    let root = tree.root();
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        assert!(tree.open(i).verify(&root, i, leaf));
+    }
+    // leaves 1 and 5 are equal, but a path only opens its own index.
+    assert!(!tree.open(1).verify(&root, 5, &leaves[5]));
+    assert!(!tree.open(1).verify(&root, 1 + 8, &leaves[1]));
+    assert!(!tree.open(1).verify(&root, 1, &leaves[2]));
+}
+
+#[test]
+fn test_fri_plookup() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let f = SampleTable::<Fr>::from_u64(vec![24, 21, 56, 56, 1]);
+
+    let fri_plookup = FriPlookUp::new(4, 4);
+    let proof = fri_plookup.prove(&f, &t);
+    assert_eq!(proof.domain_size, 8);
+    fri_plookup.verify(&proof, &t);
+}
+
+#[test]
+#[should_panic]
+fn test_fri_plookup_rejects_tampered_evaluation() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let f = SampleTable::<Fr>::from_u64(vec![24, 21, 56]);
+
+    let fri_plookup = FriPlookUp::new(4, 4);
+    let mut proof = fri_plookup.prove(&f, &t);
+    proof.evaluations[0][0] += Fr::from(1u64);
+    fri_plookup.verify(&proof, &t);
+}
+
+#[test]
+#[should_panic]
+fn test_fri_plookup_rejects_another_table() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let f = SampleTable::<Fr>::from_u64(vec![24, 21, 56]);
+
+    let fri_plookup = FriPlookUp::new(4, 4);
+    let proof = fri_plookup.prove(&f, &t);
+    let other = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 99]);
+    fri_plookup.verify(&proof, &other);
+}
+
+#[test]
+#[should_panic]
+fn test_fri_plookup_rejects_tampered_query() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let f = SampleTable::<Fr>::from_u64(vec![24, 21, 56]);
+
+    let fri_plookup = FriPlookUp::new(4, 4);
+    let mut proof = fri_plookup.prove(&f, &t);
+    proof.queries[0][3].0 += Fr::from(1u64);
+    fri_plookup.verify(&proof, &t);
+}
+
+#[test]
+#[should_panic]
+fn test_fri_plookup_rejects_query_at_another_index() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let f = SampleTable::<Fr>::from_u64(vec![24, 21, 56]);
+
+    let fri_plookup = FriPlookUp::new(4, 4);
+    let mut proof = fri_plookup.prove(&f, &t);
+    // valid openings of the codewords, but at the indexes of another query.
+    proof.queries[0] = proof.queries[1].clone();
+    fri_plookup.verify(&proof, &t);
+}
@@ -0,0 +1,147 @@
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+
+use crate::{error::KzgError, srs::VerifierKey};
+
+/// Deferred KZG pairing checks.
+///
+/// Every opening claim reduces to e(left, [1]_2) = e(right, [τ]_2), so claims are merged
+/// with random weights and discharged together by `check` with a single multi-pairing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PairingAccumulator<G: CurveGroup> {
+    left: G,
+    right: G,
+}
+
+impl<G: CurveGroup> Default for PairingAccumulator<G> {
+    fn default() -> Self {
+        PairingAccumulator {
+            left: G::zero(),
+            right: G::zero(),
+        }
+    }
+}
+
+impl<G: CurveGroup> PairingAccumulator<G> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the claim e(left, [1]_2) = e(right, [τ]_2).
+    pub fn add_claim<R: Rng>(&mut self, left: &G, right: &G, rng: &mut R) {
+        let r = G::ScalarField::rand(rng);
+        self.left.add_assign(left.mul(r));
+        self.right.add_assign(right.mul(r));
+    }
+
+    /// Adds all the claims of `other`.
+    pub fn merge<R: Rng>(&mut self, other: &Self, rng: &mut R) {
+        self.add_claim(&other.left, &other.right, rng);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_zero() && self.right.is_zero()
+    }
+
+    /// Checks every accumulated claim at once.
+    pub fn check<P: Pairing<G1 = G>>(&self, vk: &VerifierKey<P>) -> Result<(), KzgError> {
+        let is_ok = P::multi_pairing([self.left, self.right.neg()], [vk.g2, vk.g2_tau]).is_zero();
+        if !is_ok {
+            return Err(KzgError::PairingCheckFailed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_accumulator {
+    use super::PairingAccumulator;
+    use crate::{commitment::KZGCommitmentScheme, error::KzgError, srs::SRS};
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_ff::One;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{test_rng, UniformRand};
+    use merlin::Transcript;
+
+    #[test]
+    fn test_pairing_accumulator() {
+        let max_degree = 16;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+        let polys = (0..3)
+            .map(|_| DensePolynomial::<Fr>::rand(max_degree, &mut rng))
+            .collect::<Vec<_>>();
+        let poly_comms = polys
+            .iter()
+            .map(|poly| kzg_comm_scheme.commit(poly).unwrap())
+            .collect::<Vec<_>>();
+        let z = Fr::rand(&mut rng);
+
+        let mut accumulator = PairingAccumulator::<G1Projective>::new();
+        assert!(accumulator.check(&vk).is_ok());
+
+        let proof = kzg_comm_scheme.prove(&polys[0], &z).unwrap();
+        vk.accumulate(&poly_comms[0], &proof, &z, &mut accumulator, &mut rng)
+            .unwrap();
+
+        let mut transcript = Transcript::new(b"test");
+        let batch_proof = kzg_comm_scheme
+            .batch_prove(&polys, &poly_comms, &z, &mut transcript)
+            .unwrap();
+        vk.batch_accumulate(
+            std::slice::from_ref(&poly_comms),
+            &[&batch_proof],
+            &[z],
+            &mut Transcript::new(b"test"),
+            &mut accumulator,
+            &mut rng,
+        )
+        .unwrap();
+
+        let points = vec![vec![z], vec![z, Fr::rand(&mut rng)], vec![Fr::one()]];
+        let mut transcript = Transcript::new(b"test");
+        let (multi_proof, evaluations) = kzg_comm_scheme
+            .multi_open(&polys, &poly_comms, &points, &mut transcript)
+            .unwrap();
+        let mut transcript = Transcript::new(b"test");
+        vk.multi_accumulate(
+            &poly_comms,
+            &points,
+            &evaluations,
+            &multi_proof,
+            &mut transcript,
+            &mut accumulator,
+            &mut rng,
+        )
+        .unwrap();
+        assert!(accumulator.check(&vk).is_ok());
+
+        // carry the accumulator over to another batch.
+        let mut buf = vec![];
+        accumulator.serialize_compressed(&mut buf).unwrap();
+        let mut restored = PairingAccumulator::deserialize_compressed(&buf[..]).unwrap();
+        assert_eq!(restored, accumulator);
+
+        let mut next = PairingAccumulator::new();
+        let proof = kzg_comm_scheme.prove(&polys[2], &z).unwrap();
+        vk.accumulate(&poly_comms[2], &proof, &z, &mut next, &mut rng)
+            .unwrap();
+        restored.merge(&next, &mut rng);
+        assert!(restored.check(&vk).is_ok());
+
+        // a single wrong claim spoils the whole accumulator.
+        let wrong_z = Fr::rand(&mut rng);
+        vk.accumulate(&poly_comms[2], &proof, &wrong_z, &mut restored, &mut rng)
+            .unwrap();
+        assert!(matches!(
+            restored.check(&vk),
+            Err(KzgError::PairingCheckFailed)
+        ));
+    }
+}
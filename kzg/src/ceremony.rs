@@ -0,0 +1,299 @@
+use ark_ec::{pairing::Pairing, Group};
+use ark_ff::{One, UniformRand, Zero};
+use ark_std::rand::Rng;
+use merlin::Transcript;
+use std::ops::{Add, Mul, MulAssign};
+
+use crate::{error::KzgError, srs::SRS, transcripts::GlobalTranscript};
+
+/// Evidence that a contributor multiplied the secret of the SRS by a secret `s` it knows.
+#[derive(Debug, Clone)]
+pub struct UpdateProof<P: Pairing> {
+    /// [s]_1
+    s_g1: P::G1,
+    /// [s]_2
+    s_g2: P::G2,
+    /// Schnorr proof of knowledge of `s`: pok_r = [k]_1, pok_z = k + c * s.
+    pok_r: P::G1,
+    pok_z: P::ScalarField,
+    /// g1[1] of the SRS after this contribution.
+    tau_g1: P::G1,
+}
+
+/// A multi-party ceremony, every contributor updates the SRS with its own secret,
+/// the SRS is secure as long as one contributor discards its secret.
+pub struct Ceremony<P: Pairing> {
+    pub srs: SRS<P>,
+    pub contributions: Vec<UpdateProof<P>>,
+    /// g1[1] of the SRS before the first contribution.
+    initial_tau_g1: P::G1,
+}
+
+impl<P: Pairing> Ceremony<P> {
+    /// The SRS should have at least [τ]_1 and [τ]_2.
+    pub fn new(srs: SRS<P>) -> Result<Self, KzgError> {
+        if srs.g1.is_empty() {
+            return Err(KzgError::EmptySrs);
+        }
+        for len in [srs.g1.len(), srs.g2.len()] {
+            if len < 2 {
+                return Err(KzgError::LengthMismatch {
+                    expected: 2,
+                    found: len,
+                });
+            }
+        }
+
+        let initial_tau_g1 = srs.g1[1];
+        Ok(Ceremony {
+            srs,
+            contributions: vec![],
+            initial_tau_g1,
+        })
+    }
+
+    /// Updates the SRS with a fresh secret and records the proof of the contribution.
+    pub fn contribute<R: Rng>(&mut self, rng: &mut R) -> &UpdateProof<P> {
+        let s = P::ScalarField::rand(rng);
+        self.contribute_with_secret(&s, rng)
+    }
+
+    fn contribute_with_secret<R: Rng>(
+        &mut self,
+        s: &P::ScalarField,
+        rng: &mut R,
+    ) -> &UpdateProof<P> {
+        let prev_tau_g1 = self.srs.g1[1];
+        self.srs.update_with_secret(s);
+
+        let s_g1 = P::G1::generator().mul(s);
+        let s_g2 = P::G2::generator().mul(s);
+        let k = P::ScalarField::rand(rng);
+        let pok_r = P::G1::generator().mul(k);
+        let c = pok_challenge::<P>(&prev_tau_g1, self.contributions.len(), &s_g1, &pok_r);
+        let pok_z = k.add(c.mul(s));
+
+        self.contributions.push(UpdateProof {
+            s_g1,
+            s_g2,
+            pok_r,
+            pok_z,
+            tau_g1: self.srs.g1[1],
+        });
+        self.contributions.last().unwrap()
+    }
+
+    /// Replays the whole chain of contributions and checks the final SRS.
+    pub fn verify<R: Rng>(&self, rng: &mut R) -> bool {
+        let g1_base = P::G1::generator();
+        let g2_base = P::G2::generator();
+
+        let mut tau_g1 = self.initial_tau_g1;
+        for (index, proof) in self.contributions.iter().enumerate() {
+            if !verify_contribution(&tau_g1, index, proof) {
+                return false;
+            }
+            tau_g1 = proof.tau_g1;
+        }
+
+        let srs = &self.srs;
+        if srs.g1.len() < 2 || srs.g2.len() < 2 {
+            return false;
+        }
+        if srs.g1[0] != g1_base || srs.g2[0] != g2_base || srs.g1[1] != tau_g1 {
+            return false;
+        }
+
+        // e(g1[i + 1], g2[0]) = e(g1[i], g2[1]) and e(h[i + 1], g2[0]) = e(h[i], g2[1]),
+        // checked at once with a random linear combination.
+        let r = P::ScalarField::rand(rng);
+        let mut r_pow = P::ScalarField::one();
+        let mut left = P::G1::zero();
+        let mut right = P::G1::zero();
+        for powers in [&srs.g1, &srs.h] {
+            for i in 1..powers.len() {
+                left += powers[i].mul(r_pow);
+                right += powers[i - 1].mul(r_pow);
+                r_pow.mul_assign(&r);
+            }
+        }
+
+        if P::pairing(left, srs.g2[0]) != P::pairing(right, srs.g2[1]) {
+            return false;
+        }
+
+        // e(g1[0], g2[i + 1]) = e(g1[1], g2[i]), with another random linear combination.
+        let mut r_pow = P::ScalarField::one();
+        let mut left = P::G2::zero();
+        let mut right = P::G2::zero();
+        for i in 1..srs.g2.len() {
+            left += srs.g2[i].mul(r_pow);
+            right += srs.g2[i - 1].mul(r_pow);
+            r_pow.mul_assign(&r);
+        }
+
+        P::pairing(srs.g1[0], left) == P::pairing(srs.g1[1], right)
+    }
+}
+
+/// Checks that `proof.tau_g1` is `prev_tau_g1` multiplied by a known, non-zero secret,
+/// `index` being the position of the contribution in the ceremony.
+pub fn verify_contribution<P: Pairing>(
+    prev_tau_g1: &P::G1,
+    index: usize,
+    proof: &UpdateProof<P>,
+) -> bool {
+    let g1_base = P::G1::generator();
+    let g2_base = P::G2::generator();
+
+    // 1. the secret is not zero.
+    if proof.s_g1.is_zero() || proof.s_g2.is_zero() || proof.tau_g1.is_zero() {
+        return false;
+    }
+
+    // 2. the contributor knows s.
+    let c = pok_challenge::<P>(prev_tau_g1, index, &proof.s_g1, &proof.pok_r);
+    if g1_base.mul(proof.pok_z) != proof.pok_r.add(proof.s_g1.mul(c)) {
+        return false;
+    }
+
+    // 3. [s]_1 and [s]_2 share the same s.
+    if P::pairing(proof.s_g1, g2_base) != P::pairing(g1_base, proof.s_g2) {
+        return false;
+    }
+
+    // 4. the new secret is the previous one multiplied by s.
+    P::pairing(proof.tau_g1, g2_base) == P::pairing(prev_tau_g1, proof.s_g2)
+}
+
+/// The challenge is bound to the SRS being updated and to the position of the contribution,
+/// so a proof of knowledge cannot be replayed by another contributor.
+fn pok_challenge<P: Pairing>(
+    prev_tau_g1: &P::G1,
+    index: usize,
+    s_g1: &P::G1,
+    pok_r: &P::G1,
+) -> P::ScalarField {
+    let mut transcript = Transcript::new(b"srs update");
+    transcript.append_commitment(prev_tau_g1);
+    transcript.append_u64(b"index", index as u64);
+    transcript.append_commitment(s_g1);
+    transcript.append_commitment(pok_r);
+    <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"c")
+}
+
+#[cfg(test)]
+mod test_ceremony {
+    use super::Ceremony;
+    use crate::{commitment::KZGCommitmentScheme, error::KzgError, srs::SRS};
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+    use ark_ec::Group;
+    use ark_ff::{One, Zero};
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_std::{test_rng, UniformRand};
+    use std::ops::Mul;
+
+    #[test]
+    fn test_ceremony() {
+        let max_degree = 16;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let mut ceremony = Ceremony::new(srs).unwrap();
+        for _ in 0..3 {
+            ceremony.contribute(&mut rng);
+        }
+        assert!(ceremony.verify(&mut rng));
+
+        let (pk, vk) = ceremony.srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+        let poly = DensePolynomial::<Fr>::rand(max_degree, &mut rng);
+        let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
+        let z = Fr::rand(&mut rng);
+        let proof = kzg_comm_scheme.prove(&poly, &z).unwrap();
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+    }
+
+    #[test]
+    fn test_ceremony_with_g2_powers() {
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new_with_g2_powers(8, 8, &mut rng);
+        let mut ceremony = Ceremony::new(srs).unwrap();
+        for _ in 0..2 {
+            ceremony.contribute(&mut rng);
+        }
+        assert!(ceremony.verify(&mut rng));
+
+        // g2[4] is not a power of the secret.
+        ceremony.srs.g2[4] += G2Projective::generator();
+        assert!(!ceremony.verify(&mut rng));
+    }
+
+    #[test]
+    fn test_ceremony_rejects_degree_zero_srs() {
+        let mut rng = test_rng();
+        let mut srs = SRS::<Bls12_381>::new(0, &mut rng);
+        assert!(matches!(
+            Ceremony::new(srs.clone()),
+            Err(KzgError::LengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+        srs.g1.clear();
+        assert!(matches!(Ceremony::new(srs), Err(KzgError::EmptySrs)));
+    }
+
+    #[test]
+    fn test_ceremony_rejects_zero_secret() {
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(8, &mut rng);
+        let mut ceremony = Ceremony::new(srs).unwrap();
+        ceremony.contribute(&mut rng);
+        ceremony.contribute_with_secret(&Fr::zero(), &mut rng);
+        assert!(!ceremony.verify(&mut rng));
+    }
+
+    #[test]
+    fn test_ceremony_rejects_broken_powers() {
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(8, &mut rng);
+
+        // g1[5] and h[3] are not powers of the secret.
+        let mut ceremony = Ceremony::new(srs.clone()).unwrap();
+        ceremony.contribute(&mut rng);
+        ceremony.srs.g1[5] = G1Projective::generator().mul(Fr::rand(&mut rng));
+        assert!(!ceremony.verify(&mut rng));
+
+        let mut ceremony = Ceremony::new(srs.clone()).unwrap();
+        ceremony.contribute(&mut rng);
+        ceremony.srs.h[3] += G1Projective::generator();
+        assert!(!ceremony.verify(&mut rng));
+
+        // the recorded contribution does not match the SRS.
+        let mut ceremony = Ceremony::new(srs.clone()).unwrap();
+        ceremony.contribute(&mut rng);
+        ceremony.contribute(&mut rng);
+        ceremony.contributions.swap(0, 1);
+        assert!(!ceremony.verify(&mut rng));
+
+        // a contribution without the knowledge of its secret.
+        let mut ceremony = Ceremony::new(srs.clone()).unwrap();
+        ceremony.contribute(&mut rng);
+        ceremony.contributions[0].pok_z += Fr::one();
+        assert!(!ceremony.verify(&mut rng));
+
+        // the proof of knowledge of another contribution with the same secret is replayed.
+        let s = Fr::rand(&mut rng);
+        let mut ceremony = Ceremony::new(srs).unwrap();
+        ceremony.contribute_with_secret(&s, &mut rng);
+        ceremony.contribute_with_secret(&s, &mut rng);
+        assert!(ceremony.verify(&mut rng));
+        let (pok_r, pok_z) = (
+            ceremony.contributions[0].pok_r,
+            ceremony.contributions[0].pok_z,
+        );
+        ceremony.contributions[1].pok_r = pok_r;
+        ceremony.contributions[1].pok_z = pok_z;
+        assert!(!ceremony.verify(&mut rng));
+    }
+}
@@ -1,172 +1,774 @@
-use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
-use ark_ff::{One, UniformRand};
+use ark_ec::{pairing::Pairing, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{batch_inversion, One, PrimeField, UniformRand, Zero};
 use ark_poly::{
     univariate::{DenseOrSparsePolynomial, DensePolynomial},
-    DenseUVPolynomial, Polynomial,
+    DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain,
 };
 use ark_std::rand::Rng;
-use std::ops::{AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use merlin::Transcript;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 
-use crate::srs::SRS;
+use crate::{
+    accumulator::PairingAccumulator,
+    error::KzgError,
+    srs::{LagrangeBasis, VerifierKey, SRS},
+    transcripts::GlobalTranscript,
+    utils::{divide_by_linear, divide_by_vanishing_poly},
+};
 
 pub struct KZGCommitmentScheme<'a, P: Pairing>(pub &'a SRS<P>);
 
 #[derive(Debug, Clone)]
 pub struct KZGCommitmentProof<G: CurveGroup> {
-    opening_values: Vec<G::ScalarField>,
-    comm_h: G,
+    pub(crate) opening_values: Vec<G::ScalarField>,
+    pub(crate) comm_h: G,
+}
+
+impl<G: CurveGroup> KZGCommitmentProof<G> {
+    pub fn opening_values(&self) -> &[G::ScalarField] {
+        &self.opening_values
+    }
+
+    /// Commitment to the quotient (p(X) - p(z)) / (X - z).
+    pub fn comm_h(&self) -> &G {
+        &self.comm_h
+    }
+}
+
+/// Opening proof of several polynomials, each at its own set of points (BDFG20, shplonk).
+#[derive(Debug, Clone)]
+pub struct KZGMultiOpeningProof<G: CurveGroup> {
+    pub(crate) comm_w: G,
+    pub(crate) comm_w_prime: G,
 }
 
+/// A multi-opening proof together with the evaluations it proves.
+pub type MultiOpening<G> = (KZGMultiOpeningProof<G>, Vec<Vec<<G as Group>::ScalarField>>);
+
 impl<'a, P: Pairing> KZGCommitmentScheme<'a, P> {
     fn max_degree(&self) -> usize {
         self.0.g1.len() - 1
     }
 
-    pub fn commit(&self, poly: &DensePolynomial<P::ScalarField>) -> P::G1 {
-        assert!(self.max_degree() >= poly.degree());
+    fn check_degree(&self, poly: &DensePolynomial<P::ScalarField>) -> Result<(), KzgError> {
+        if self.0.g1.is_empty() {
+            return Err(KzgError::EmptySrs);
+        }
+        if poly.degree() > self.max_degree() {
+            return Err(KzgError::DegreeTooLarge {
+                degree: poly.degree(),
+                max_degree: self.max_degree(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn commit(&self, poly: &DensePolynomial<P::ScalarField>) -> Result<P::G1, KzgError> {
+        self.check_degree(poly)?;
         let bases = P::G1::normalize_batch(&self.0.g1[..poly.coeffs().len()]);
-        P::G1::msm(&bases, &poly.coeffs()).unwrap()
+        Ok(P::G1::msm_unchecked(&bases, poly.coeffs()))
     }
 
     pub fn prove(
         &self,
         poly: &DensePolynomial<P::ScalarField>,
         z: &P::ScalarField,
-    ) -> KZGCommitmentProof<P::G1> {
-        let opening_value = poly.evaluate(z);
+    ) -> Result<KZGCommitmentProof<P::G1>, KzgError> {
+        self.check_degree(poly)?;
+        // p(X) = q(X) * (X - z) + p(z)
+        let (quotient, opening_value) = divide_by_linear(poly, z);
 
-        let dividend = poly.sub(&DensePolynomial::from_coefficients_slice(&[opening_value]));
-        let divisor = DensePolynomial::from_coefficients_slice(&[z.neg(), P::ScalarField::one()]);
+        let comm_h = self.commit(&quotient)?;
 
-        let (quotient, remainder) =
-            DenseOrSparsePolynomial::divide_with_q_and_r(&(&dividend).into(), &(&divisor).into())
-                .unwrap();
+        Ok(KZGCommitmentProof {
+            opening_values: vec![opening_value],
+            comm_h,
+        })
+    }
 
-        assert!(remainder.is_empty());
+    /// Commits to the polynomial whose evaluations over `basis.domain` are `evals`.
+    pub fn commit_evaluations(
+        &self,
+        basis: &LagrangeBasis<P>,
+        evals: &[P::ScalarField],
+    ) -> Result<P::G1, KzgError> {
+        check_evaluations(basis, evals)?;
+        let bases = P::G1::normalize_batch(&basis.g1[..evals.len()]);
+        Ok(P::G1::msm_unchecked(&bases, evals))
+    }
 
-        let comm_h = self.commit(&quotient);
+    /// Same as `prove`, but the polynomial is given by its evaluations over `basis.domain`.
+    pub fn prove_evaluations(
+        &self,
+        basis: &LagrangeBasis<P>,
+        evals: &[P::ScalarField],
+        z: &P::ScalarField,
+    ) -> Result<KZGCommitmentProof<P::G1>, KzgError> {
+        check_evaluations(basis, evals)?;
+        let (opening_value, quotient_evals) = divide_evaluations(&basis.domain, evals, z);
+        let comm_h = self.commit_evaluations(basis, &quotient_evals)?;
 
-        KZGCommitmentProof {
+        Ok(KZGCommitmentProof {
             opening_values: vec![opening_value],
             comm_h,
+        })
+    }
+
+    /// Same as `batch_prove`, but the polynomials are given by their evaluations over `basis.domain`.
+    pub fn batch_prove_evaluations(
+        &self,
+        basis: &LagrangeBasis<P>,
+        evals: &[Vec<P::ScalarField>],
+        poly_comms: &[P::G1],
+        z: &P::ScalarField,
+        transcript: &mut Transcript,
+    ) -> Result<KZGCommitmentProof<P::G1>, KzgError> {
+        check_commitments(evals.len(), poly_comms)?;
+        let mut opening_values = vec![];
+        let mut quotients_evals = vec![];
+        for evals in evals.iter() {
+            check_evaluations(basis, evals)?;
+            let (opening_value, quotient_evals) = divide_evaluations(&basis.domain, evals, z);
+            opening_values.push(opening_value);
+            quotients_evals.push(quotient_evals);
+        }
+        let challenge = batch_challenge(transcript, poly_comms, z, &opening_values);
+
+        let mut v = P::ScalarField::one();
+        let mut quotient_evals_sum = vec![P::ScalarField::zero(); basis.domain.size()];
+        for quotient_evals in quotients_evals.iter() {
+            for (x, q) in quotient_evals_sum.iter_mut().zip(quotient_evals.iter()) {
+                x.add_assign(q.mul(&v));
+            }
+            v.mul_assign(&challenge);
+        }
+
+        let comm_h = self.commit_evaluations(basis, &quotient_evals_sum)?;
+        transcript.append_commitment(&comm_h);
+
+        Ok(KZGCommitmentProof {
+            opening_values,
+            comm_h,
+        })
+    }
+
+    /// Opens `poly` at every point of `domain` with the FK20 technique,
+    /// `proofs[i]` opens `poly` at `domain.element(i)`.
+    ///
+    /// The quotient at z is [q_z(τ)] = \sum_k z^k * h_k with h_k = \sum_j c_{j + k + 1} * [τ^j],
+    /// so the proofs are the FFT of h, and h is a toeplitz product computed with one convolution.
+    pub fn open_all_on_domain(
+        &self,
+        poly: &DensePolynomial<P::ScalarField>,
+        domain: &Radix2EvaluationDomain<P::ScalarField>,
+    ) -> Result<Vec<KZGCommitmentProof<P::G1>>, KzgError> {
+        self.check_degree(poly)?;
+        let d = poly.degree();
+
+        // 1. h_k = (c * reversed([τ^0], ..., [τ^{d - 1}]))_{k + d}, for k in 0..d
+        let mut h = vec![];
+        if d > 0 {
+            let conv_domain = Radix2EvaluationDomain::<P::ScalarField>::new(2 * d).ok_or(
+                KzgError::DegreeTooLarge {
+                    degree: d,
+                    max_degree: self.max_degree(),
+                },
+            )?;
+            let mut powers = self.0.g1[..d].to_vec();
+            powers.reverse();
+            let powers_evals = conv_domain.fft(&powers);
+            let coeffs_evals = conv_domain.fft(poly.coeffs());
+            let conv_evals = powers_evals
+                .iter()
+                .zip(coeffs_evals.iter())
+                .map(|(x, c)| x.mul(c))
+                .collect::<Vec<_>>();
+            h = conv_domain.ifft(&conv_evals)[d..2 * d].to_vec();
         }
+
+        // 2. evaluate at every ω^i, ω^n = 1 so the terms of degree >= n wrap around.
+        let comms_h = domain.fft(&fold(&h, domain.size()));
+        let opening_values = domain.fft(&fold(poly.coeffs(), domain.size()));
+
+        Ok(opening_values
+            .into_iter()
+            .zip(comms_h)
+            .map(|(opening_value, comm_h)| KZGCommitmentProof {
+                opening_values: vec![opening_value],
+                comm_h,
+            })
+            .collect())
     }
 
+    /// Opens several polynomials at the same point `z`.
+    ///
+    /// The dividends `p_i(X) - p_i(z)` are combined with the powers of a challenge derived
+    /// from `transcript` after `poly_comms`, the commitments of `polys`, `z` and the evaluations.
     pub fn batch_prove(
         &self,
         polys: &[DensePolynomial<P::ScalarField>],
+        poly_comms: &[P::G1],
         z: &P::ScalarField,
-        _challenge: &P::ScalarField,
-    ) -> KZGCommitmentProof<P::G1> {
-        //let mut v = Fr::one();
-        let mut dividend_sum = DensePolynomial::default();
+        transcript: &mut Transcript,
+    ) -> Result<KZGCommitmentProof<P::G1>, KzgError> {
+        check_commitments(polys.len(), poly_comms)?;
         let mut opening_values = vec![];
         for poly in polys.iter() {
-            let opening_value = poly.evaluate(z);
-            opening_values.push(opening_value);
-            let dividend = poly.sub(&DensePolynomial::from_coefficients_slice(&[opening_value]));
-            //  let dividend = dividend.mul(v);
-            dividend_sum.add_assign(&dividend);
-            //  v.mul_assign(challenge);
+            self.check_degree(poly)?;
+            opening_values.push(poly.evaluate(z));
         }
+        let challenge = batch_challenge(transcript, poly_comms, z, &opening_values);
 
-        let divisor = DensePolynomial::from_coefficients_slice(&[z.neg(), P::ScalarField::one()]);
-
-        let (quotient, remainder) = DenseOrSparsePolynomial::divide_with_q_and_r(
-            &(&dividend_sum).into(),
-            &(&divisor).into(),
-        )
-        .unwrap();
+        let mut v = P::ScalarField::one();
+        let mut dividend_sum = DensePolynomial::default();
+        for (poly, opening_value) in polys.iter().zip(opening_values.iter()) {
+            let dividend = poly.sub(&DensePolynomial::from_coefficients_slice(&[*opening_value]));
+            dividend_sum.add_assign(&dividend.mul(v));
+            v.mul_assign(&challenge);
+        }
 
-        assert!(remainder.is_empty());
+        let (quotient, remainder) = divide_by_linear(&dividend_sum, z);
+        debug_assert!(remainder.is_zero());
 
-        let comm_h = self.commit(&quotient);
+        let comm_h = self.commit(&quotient)?;
+        transcript.append_commitment(&comm_h);
 
-        KZGCommitmentProof {
+        Ok(KZGCommitmentProof {
             opening_values,
             comm_h,
+        })
+    }
+
+    /// Opens `polys[i]` at every point of `points[i]` and returns the proof together with
+    /// the evaluations, `evaluations[i][j] = polys[i](points[i][j])`.
+    ///
+    /// `poly_comms`, the commitments of `polys`, are appended to `transcript` with the points
+    /// and the evaluations before the challenges are derived.
+    pub fn multi_open(
+        &self,
+        polys: &[DensePolynomial<P::ScalarField>],
+        poly_comms: &[P::G1],
+        points: &[Vec<P::ScalarField>],
+        transcript: &mut Transcript,
+    ) -> Result<MultiOpening<P::G1>, KzgError> {
+        check_commitments(polys.len(), poly_comms)?;
+        if polys.len() != points.len() {
+            return Err(KzgError::LengthMismatch {
+                expected: polys.len(),
+                found: points.len(),
+            });
+        }
+        for (poly, points) in polys.iter().zip(points.iter()) {
+            self.check_degree(poly)?;
+            check_distinct_points(points)?;
         }
+
+        let evaluations = polys
+            .iter()
+            .zip(points.iter())
+            .map(|(poly, points)| points.iter().map(|x| poly.evaluate(x)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let gamma = multi_challenge(transcript, poly_comms, points, &evaluations);
+
+        // 1. h(X) = \sum gamma^i * (f_i(X) - r_i(X)) / Z_{S_i}(X)
+        let mut gamma_pow = P::ScalarField::one();
+        let mut h_poly = DensePolynomial::default();
+        for ((poly, points), evals) in polys.iter().zip(points.iter()).zip(evaluations.iter()) {
+            let dividend = poly.sub(&interpolate(points, evals));
+            let quotient = divide_by_vanishing_poly(&dividend, points).unwrap_or_else(|| {
+                let divisor = vanishing_poly(points);
+                DenseOrSparsePolynomial::divide_with_q_and_r(
+                    &(&dividend).into(),
+                    &(&divisor).into(),
+                )
+                .unwrap()
+                .0
+            });
+
+            h_poly.add_assign(&quotient.mul(gamma_pow));
+            gamma_pow.mul_assign(&gamma);
+        }
+        let comm_w = self.commit(&h_poly)?;
+        transcript.append_commitment(&comm_w);
+        let z = <Transcript as GlobalTranscript<P::G1>>::get_challenge(transcript, b"z");
+
+        // 2. L(X) = \sum gamma^i * Z_{T \ S_i}(z) * (f_i(X) - r_i(z)) - Z_T(z) * h(X)
+        let all_points = union_points(points);
+        let mut gamma_pow = P::ScalarField::one();
+        let mut l_poly = DensePolynomial::default();
+        for ((poly, points), evals) in polys.iter().zip(points.iter()).zip(evaluations.iter()) {
+            let r_eval = evaluate_interpolation(points, evals, &z);
+            let coeff = evaluate_vanishing_poly(&complement_points(&all_points, points), &z);
+            let term = poly.sub(&DensePolynomial::from_coefficients_slice(&[r_eval]));
+            l_poly.add_assign(&term.mul(gamma_pow.mul(&coeff)));
+            gamma_pow.mul_assign(&gamma);
+        }
+        let l_poly = l_poly.sub(&h_poly.mul(evaluate_vanishing_poly(&all_points, &z)));
+
+        // 3. L(z) = 0, so L(X) is divisible by (X - z).
+        let (quotient, remainder) = divide_by_linear(&l_poly, &z);
+        debug_assert!(remainder.is_zero());
+
+        let comm_w_prime = self.commit(&quotient)?;
+        transcript.append_commitment(&comm_w_prime);
+
+        Ok((
+            KZGMultiOpeningProof {
+                comm_w,
+                comm_w_prime,
+            },
+            evaluations,
+        ))
     }
+}
 
+impl<P: Pairing> VerifierKey<P> {
     pub fn verify(
         &self,
         poly_comm: &P::G1,
         proof: &KZGCommitmentProof<P::G1>,
         z: &P::ScalarField,
-    ) -> bool {
-        let g1_base = self.0.g1[0];
-        let g2_base = self.0.g2[0];
-        let g2_r = self.0.g2[1];
-
-        let left = P::pairing(poly_comm.sub(g1_base.mul(proof.opening_values[0])), g2_base);
-        let right = P::pairing(proof.comm_h, g2_r.sub(g2_base.mul(z)));
+    ) -> Result<(), KzgError> {
+        let (left, right) = self.claim(poly_comm, proof, z)?;
+        self.check_claim(&left, &right)
+    }
 
-        left == right
+    /// Same as `verify`, but the pairing check is deferred to `accumulator`.
+    pub fn accumulate<R: Rng>(
+        &self,
+        poly_comm: &P::G1,
+        proof: &KZGCommitmentProof<P::G1>,
+        z: &P::ScalarField,
+        accumulator: &mut PairingAccumulator<P::G1>,
+        rng: &mut R,
+    ) -> Result<(), KzgError> {
+        let (left, right) = self.claim(poly_comm, proof, z)?;
+        accumulator.add_claim(&left, &right, rng);
+        Ok(())
     }
 
+    /// Verifies several batch proofs at once.
+    ///
+    /// `proofs` should have been produced in order by `batch_prove` on a single transcript,
+    /// `proofs[i]` opening the polynomials committed in `poly_comms[i]` at `zs[i]`. The
+    /// transcript is replayed to derive every challenge.
     pub fn batch_verify<R: Rng>(
         &self,
         poly_comms: &[Vec<P::G1>],
         proofs: &[&KZGCommitmentProof<P::G1>],
         zs: &[P::ScalarField],
+        transcript: &mut Transcript,
         rng: &mut R,
-    ) -> bool {
-        let g1_base = self.0.g1[0];
-        let g2_base = self.0.g2[0];
-        let g2_r = self.0.g2[1];
-
-        let mut r = P::ScalarField::rand(rng);
-        let mut left_0 = P::G1::default();
-        left_0.add_assign(&poly_comms[0].iter().sum::<P::G1>());
-        left_0.sub_assign(g1_base.mul(&proofs[0].opening_values.iter().sum::<P::ScalarField>()));
-        left_0.add_assign(proofs[0].comm_h.mul(&zs[0]));
-
-        let mut right_0 = P::G1::default();
-        right_0.add_assign(&proofs[0].comm_h);
-        for ((poly_comm, proof), z) in poly_comms.iter().zip(proofs.iter()).zip(zs.iter()).skip(1) {
-            left_0.add_assign(poly_comm.iter().sum::<P::G1>().mul(&r));
-            let opening_values_sum = proof.opening_values.iter().sum::<P::ScalarField>();
-            left_0.sub_assign(g1_base.mul(&opening_values_sum).mul(&r));
-            left_0.add_assign(proof.comm_h.mul(z.mul(&r)));
-
-            right_0.add_assign(&proof.comm_h.mul(&r));
-
-            r.mul_assign(&r.clone());
+    ) -> Result<(), KzgError> {
+        let (left, right) = self.batch_claim(poly_comms, proofs, zs, transcript, rng)?;
+        self.check_claim(&left, &right)
+    }
+
+    /// Same as `batch_verify`, but the pairing check is deferred to `accumulator`.
+    pub fn batch_accumulate<R: Rng>(
+        &self,
+        poly_comms: &[Vec<P::G1>],
+        proofs: &[&KZGCommitmentProof<P::G1>],
+        zs: &[P::ScalarField],
+        transcript: &mut Transcript,
+        accumulator: &mut PairingAccumulator<P::G1>,
+        rng: &mut R,
+    ) -> Result<(), KzgError> {
+        let (left, right) = self.batch_claim(poly_comms, proofs, zs, transcript, rng)?;
+        accumulator.add_claim(&left, &right, rng);
+        Ok(())
+    }
+
+    /// Verifies a proof created by `multi_open` with a single pairing equation.
+    pub fn multi_verify(
+        &self,
+        poly_comms: &[P::G1],
+        points: &[Vec<P::ScalarField>],
+        evaluations: &[Vec<P::ScalarField>],
+        proof: &KZGMultiOpeningProof<P::G1>,
+        transcript: &mut Transcript,
+    ) -> Result<(), KzgError> {
+        let (left, right) = self.multi_claim(poly_comms, points, evaluations, proof, transcript)?;
+        self.check_claim(&left, &right)
+    }
+
+    /// Same as `multi_verify`, but the pairing check is deferred to `accumulator`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn multi_accumulate<R: Rng>(
+        &self,
+        poly_comms: &[P::G1],
+        points: &[Vec<P::ScalarField>],
+        evaluations: &[Vec<P::ScalarField>],
+        proof: &KZGMultiOpeningProof<P::G1>,
+        transcript: &mut Transcript,
+        accumulator: &mut PairingAccumulator<P::G1>,
+        rng: &mut R,
+    ) -> Result<(), KzgError> {
+        let (left, right) = self.multi_claim(poly_comms, points, evaluations, proof, transcript)?;
+        accumulator.add_claim(&left, &right, rng);
+        Ok(())
+    }
+
+    /// Checks e(left, [1]_2) = e(right, [τ]_2), the form every opening claim is reduced to.
+    pub fn check_claim(&self, left: &P::G1, right: &P::G1) -> Result<(), KzgError> {
+        if P::pairing(left, self.g2) != P::pairing(right, self.g2_tau) {
+            return Err(KzgError::PairingCheckFailed);
+        }
+        Ok(())
+    }
+
+    /// e(comm - [y], [1]_2) = e(comm_h, [τ - z]_2), that is
+    /// e(comm - [y] + z * comm_h, [1]_2) = e(comm_h, [τ]_2).
+    fn claim(
+        &self,
+        poly_comm: &P::G1,
+        proof: &KZGCommitmentProof<P::G1>,
+        z: &P::ScalarField,
+    ) -> Result<(P::G1, P::G1), KzgError> {
+        if proof.opening_values.len() != 1 {
+            return Err(KzgError::MalformedProof);
         }
 
-        let left = P::pairing(left_0, g2_base);
+        let left = poly_comm
+            .sub(self.g1.mul(proof.opening_values[0]))
+            .add(proof.comm_h.mul(z));
 
-        let right = P::pairing(right_0, g2_r);
+        Ok((left, proof.comm_h))
+    }
+
+    fn batch_claim<R: Rng>(
+        &self,
+        poly_comms: &[Vec<P::G1>],
+        proofs: &[&KZGCommitmentProof<P::G1>],
+        zs: &[P::ScalarField],
+        transcript: &mut Transcript,
+        rng: &mut R,
+    ) -> Result<(P::G1, P::G1), KzgError> {
+        for len in [proofs.len(), zs.len()] {
+            if len != poly_comms.len() {
+                return Err(KzgError::LengthMismatch {
+                    expected: poly_comms.len(),
+                    found: len,
+                });
+            }
+        }
+
+        let r = P::ScalarField::rand(rng);
+        let mut r_pow = P::ScalarField::one();
+        let mut left = P::G1::default();
+        let mut right = P::G1::default();
+        for ((poly_comm, proof), z) in poly_comms.iter().zip(proofs.iter()).zip(zs.iter()) {
+            if poly_comm.len() != proof.opening_values.len() {
+                return Err(KzgError::MalformedProof);
+            }
+            let challenge = batch_challenge(transcript, poly_comm, z, &proof.opening_values);
+            transcript.append_commitment(&proof.comm_h);
+
+            // \sum v^i * (comm_i - [y_i]) + z * comm_h
+            let mut v = P::ScalarField::one();
+            let mut comm_sum = P::G1::default();
+            let mut opening_values_sum = P::ScalarField::default();
+            for (comm, opening_value) in poly_comm.iter().zip(proof.opening_values.iter()) {
+                comm_sum.add_assign(comm.mul(&v));
+                opening_values_sum.add_assign(opening_value.mul(&v));
+                v.mul_assign(&challenge);
+            }
+            comm_sum.sub_assign(self.g1.mul(&opening_values_sum));
+            comm_sum.add_assign(proof.comm_h.mul(z));
+
+            left.add_assign(comm_sum.mul(&r_pow));
+            right.add_assign(proof.comm_h.mul(&r_pow));
+
+            r_pow.mul_assign(&r);
+        }
+
+        Ok((left, right))
+    }
+
+    /// e(F + z * comm_w', [1]_2) = e(comm_w', [τ]_2)
+    fn multi_claim(
+        &self,
+        poly_comms: &[P::G1],
+        points: &[Vec<P::ScalarField>],
+        evaluations: &[Vec<P::ScalarField>],
+        proof: &KZGMultiOpeningProof<P::G1>,
+        transcript: &mut Transcript,
+    ) -> Result<(P::G1, P::G1), KzgError> {
+        if poly_comms.len() != points.len() {
+            return Err(KzgError::LengthMismatch {
+                expected: poly_comms.len(),
+                found: points.len(),
+            });
+        }
+        if points.len() != evaluations.len() {
+            return Err(KzgError::MalformedProof);
+        }
+        for (points, evals) in points.iter().zip(evaluations.iter()) {
+            check_distinct_points(points)?;
+            if points.len() != evals.len() {
+                return Err(KzgError::MalformedProof);
+            }
+        }
+
+        let gamma = multi_challenge(transcript, poly_comms, points, evaluations);
+        transcript.append_commitment(&proof.comm_w);
+        let z = <Transcript as GlobalTranscript<P::G1>>::get_challenge(transcript, b"z");
+        transcript.append_commitment(&proof.comm_w_prime);
+
+        // F = \sum gamma^i * Z_{T \ S_i}(z) * (comm_i - [r_i(z)]) - Z_T(z) * comm_w
+        let all_points = union_points(points);
+        let mut gamma_pow = P::ScalarField::one();
+        let mut f = P::G1::default();
+        for ((comm, points), evals) in poly_comms.iter().zip(points.iter()).zip(evaluations.iter())
+        {
+            let r_eval = evaluate_interpolation(points, evals, &z);
+            let coeff = evaluate_vanishing_poly(&complement_points(&all_points, points), &z);
+            let term = comm.sub(self.g1.mul(&r_eval));
+            f.add_assign(term.mul(gamma_pow.mul(&coeff)));
+            gamma_pow.mul_assign(&gamma);
+        }
+        f.sub_assign(proof.comm_w.mul(evaluate_vanishing_poly(&all_points, &z)));
+
+        Ok((f.add(proof.comm_w_prime.mul(&z)), proof.comm_w_prime))
+    }
+}
 
-        left == right
+/// The challenge combining a batch opened at `z`, derived after the commitments, `z` and the
+/// evaluations.
+fn batch_challenge<G: CurveGroup>(
+    transcript: &mut Transcript,
+    poly_comms: &[G],
+    z: &G::ScalarField,
+    opening_values: &[G::ScalarField],
+) -> G::ScalarField {
+    for comm in poly_comms.iter() {
+        transcript.append_commitment(comm);
     }
+    <Transcript as GlobalTranscript<G>>::append_scalars(transcript, &[*z]);
+    <Transcript as GlobalTranscript<G>>::append_scalars(transcript, opening_values);
+    <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"v")
+}
+
+/// The challenge gamma of a multi-opening, derived after the commitments, the points and the
+/// evaluations.
+fn multi_challenge<G: CurveGroup>(
+    transcript: &mut Transcript,
+    poly_comms: &[G],
+    points: &[Vec<G::ScalarField>],
+    evaluations: &[Vec<G::ScalarField>],
+) -> G::ScalarField {
+    for comm in poly_comms.iter() {
+        transcript.append_commitment(comm);
+    }
+    for (points, evals) in points.iter().zip(evaluations.iter()) {
+        <Transcript as GlobalTranscript<G>>::append_scalars(transcript, points);
+        <Transcript as GlobalTranscript<G>>::append_scalars(transcript, evals);
+    }
+    <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"gamma")
+}
+
+fn check_commitments<G: CurveGroup>(len: usize, poly_comms: &[G]) -> Result<(), KzgError> {
+    if poly_comms.len() != len {
+        return Err(KzgError::LengthMismatch {
+            expected: len,
+            found: poly_comms.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Reduces the coefficients modulo X^n - 1.
+fn fold<T: Copy + Zero + AddAssign>(coeffs: &[T], n: usize) -> Vec<T> {
+    let mut folded = vec![T::zero(); n];
+    for (i, c) in coeffs.iter().enumerate() {
+        folded[i % n].add_assign(*c);
+    }
+    folded
+}
+
+fn check_evaluations<P: Pairing>(
+    basis: &LagrangeBasis<P>,
+    evals: &[P::ScalarField],
+) -> Result<(), KzgError> {
+    if evals.len() > basis.g1.len() {
+        return Err(KzgError::LengthMismatch {
+            expected: basis.g1.len(),
+            found: evals.len(),
+        });
+    }
+    Ok(())
+}
+
+/// The lagrange interpolation over `points` is only defined for distinct points.
+fn check_distinct_points<F: PrimeField>(points: &[F]) -> Result<(), KzgError> {
+    for (i, point) in points.iter().enumerate() {
+        if points[..i].contains(point) {
+            return Err(KzgError::DuplicatePoint);
+        }
+    }
+    Ok(())
+}
+
+/// Given the evaluations `evals` of p(X) over `domain`, returns p(z) and the evaluations
+/// of q(X) = (p(X) - p(z)) / (X - z) over `domain`.
+fn divide_evaluations<F: PrimeField>(
+    domain: &impl EvaluationDomain<F>,
+    evals: &[F],
+    z: &F,
+) -> (F, Vec<F>) {
+    let n = domain.size();
+    debug_assert!(evals.len() <= n);
+    let mut evals = evals.to_vec();
+    evals.resize(n, F::zero());
+    let elements = domain.elements().collect::<Vec<_>>();
+
+    if let Some(k) = elements.iter().position(|x| x == z) {
+        // q(ω^i) = (p(ω^i) - p(ω^k)) / (ω^i - ω^k) for i != k,
+        // q(ω^k) = p'(ω^k) = -\sum_{i != k} q(ω^i) * ω^{i - k}.
+        let mut denominators = elements.iter().map(|x| x.sub(z)).collect::<Vec<_>>();
+        denominators[k] = F::one();
+        batch_inversion(&mut denominators);
+
+        let mut quotient_evals = evals
+            .iter()
+            .zip(denominators.iter())
+            .map(|(e, d)| e.sub(&evals[k]).mul(d))
+            .collect::<Vec<_>>();
+        let z_inv = z.inverse().unwrap();
+        quotient_evals[k] = quotient_evals
+            .iter()
+            .zip(elements.iter())
+            .enumerate()
+            .filter(|(i, _)| *i != k)
+            .map(|(_, (q, x))| q.mul(x).mul(&z_inv))
+            .sum::<F>()
+            .neg();
+
+        return (evals[k], quotient_evals);
+    }
+
+    // barycentric formula: p(z) = (z^n - 1) / n * \sum p(ω^i) * ω^i / (z - ω^i)
+    let mut denominators = elements.iter().map(|x| z.sub(x)).collect::<Vec<_>>();
+    batch_inversion(&mut denominators);
+    let opening_value = evals
+        .iter()
+        .zip(elements.iter())
+        .zip(denominators.iter())
+        .map(|((e, x), d)| e.mul(x).mul(d))
+        .sum::<F>()
+        .mul(domain.evaluate_vanishing_polynomial(*z))
+        .mul(domain.size_inv());
+
+    // q(ω^i) = (p(ω^i) - p(z)) / (ω^i - z)
+    let quotient_evals = evals
+        .iter()
+        .zip(denominators.iter())
+        .map(|(e, d)| opening_value.sub(e).mul(d))
+        .collect::<Vec<_>>();
+
+    (opening_value, quotient_evals)
+}
+
+/// Returns Z_S(X) = \prod (X - s).
+fn vanishing_poly<F: PrimeField>(points: &[F]) -> DensePolynomial<F> {
+    let mut poly = DensePolynomial::from_coefficients_slice(&[F::one()]);
+    for point in points.iter() {
+        poly = poly.naive_mul(&DensePolynomial::from_coefficients_slice(&[
+            point.neg(),
+            F::one(),
+        ]));
+    }
+    poly
+}
+
+fn evaluate_vanishing_poly<F: PrimeField>(points: &[F], z: &F) -> F {
+    points.iter().map(|point| z.sub(point)).product()
+}
+
+/// Returns the lagrange interpolation of `(points[i], evals[i])`.
+fn interpolate<F: PrimeField>(points: &[F], evals: &[F]) -> DensePolynomial<F> {
+    let mut poly = DensePolynomial::default();
+    for (i, (point_i, eval_i)) in points.iter().zip(evals.iter()).enumerate() {
+        let mut numerator = DensePolynomial::from_coefficients_slice(&[F::one()]);
+        let mut denominator = F::one();
+        for (j, point_j) in points.iter().enumerate() {
+            if i != j {
+                numerator = numerator.naive_mul(&DensePolynomial::from_coefficients_slice(&[
+                    point_j.neg(),
+                    F::one(),
+                ]));
+                denominator.mul_assign(point_i.sub(point_j));
+            }
+        }
+        // `+` drops the leading zeros, `+=` keeps them when the degrees are equal.
+        poly = &poly + &numerator.mul(eval_i.mul(denominator.inverse().unwrap()));
+    }
+    poly
+}
+
+fn evaluate_interpolation<F: PrimeField>(points: &[F], evals: &[F], z: &F) -> F {
+    let mut res = F::zero();
+    for (i, (point_i, eval_i)) in points.iter().zip(evals.iter()).enumerate() {
+        let mut numerator = F::one();
+        let mut denominator = F::one();
+        for (j, point_j) in points.iter().enumerate() {
+            if i != j {
+                numerator.mul_assign(z.sub(point_j));
+                denominator.mul_assign(point_i.sub(point_j));
+            }
+        }
+        res.add_assign(eval_i.mul(numerator.mul(denominator.inverse().unwrap())));
+    }
+    res
+}
+
+fn union_points<F: PrimeField>(points: &[Vec<F>]) -> Vec<F> {
+    let mut all_points = vec![];
+    for point in points.iter().flatten() {
+        if !all_points.contains(point) {
+            all_points.push(*point);
+        }
+    }
+    all_points
+}
+
+fn complement_points<F: PrimeField>(all_points: &[F], points: &[F]) -> Vec<F> {
+    all_points
+        .iter()
+        .filter(|x| !points.contains(x))
+        .copied()
+        .collect()
 }
 
 #[cfg(test)]
 mod test_kzg {
     use super::{KZGCommitmentProof, KZGCommitmentScheme, SRS};
-    use ark_bls12_381::Bls12_381;
+    use crate::error::KzgError;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_bn254::Bn254;
     use ark_ec::pairing::Pairing;
+    use ark_ff::{One, Zero};
     use ark_poly::univariate::DensePolynomial;
-    use ark_poly::DenseUVPolynomial;
+    use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
     use ark_std::UniformRand;
     use ark_std::{rand::Rng, test_rng};
+    use merlin::Transcript;
 
     #[test]
     fn test_kzg_comm() {
         let max_degree = 20;
         let mut rng = test_rng();
         let mut srs = SRS::new(max_degree, &mut rng);
-        let srs_clone = srs.clone();
-        let kzg_comm_scheme = KZGCommitmentScheme(&srs_clone);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
         let (poly_comm, proof, z) =
             kzg_comm::<Bls12_381, _>(&kzg_comm_scheme, max_degree, &mut rng);
-        let is_ok = kzg_comm_scheme.verify(&poly_comm, &proof, &z);
+        let is_ok = vk.verify(&poly_comm, &proof, &z).is_ok();
         assert!(is_ok);
 
-        srs.update(&mut rng);
+        srs.update(&mut rng).unwrap();
         let (poly_comm, proof, z) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
-        let is_ok = kzg_comm_scheme.verify(&poly_comm, &proof, &z);
+        let is_ok = vk.verify(&poly_comm, &proof, &z).is_ok();
         assert!(is_ok);
     }
 
@@ -179,64 +781,422 @@ mod test_kzg {
     //     let pub_srs_clone = pub_srs.clone();
     //     let kzg_comm_scheme = KZGCommitmentScheme(&pub_srs_clone);
     //     let (poly_comm, proof, z) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
-    //     let is_ok = kzg_comm_scheme.verify(&poly_comm, &proof, &z);
+    //     let is_ok = vk.verify(&poly_comm, &proof, &z).is_ok();
     //     assert!(is_ok);
 
-    //     pub_srs.update(&mut rng);
+    //     pub_srs.update(&mut rng).unwrap();
     //     let (poly_comm, proof, z) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
-    //     let is_ok = kzg_comm_scheme.verify(&poly_comm, &proof, &z);
+    //     let is_ok = vk.verify(&poly_comm, &proof, &z).is_ok();
     //     assert!(is_ok);
     // }
 
     #[test]
     fn test_batch_kzg_comm() {
         let max_degree = 20;
-        let batch_size = 5;
         let mut rng = test_rng();
         let srs = SRS::new(max_degree, &mut rng);
-        let kzg_comm_scheme = KZGCommitmentScheme(&srs);
-        let (poly_comm1, proof1, z1) =
-            kzg_comm::<Bls12_381, _>(&kzg_comm_scheme, max_degree, &mut rng);
-        let is_ok = kzg_comm_scheme.verify(&poly_comm1, &proof1, &z1);
-        assert!(is_ok);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+        // the batches are proven one after the other on the same transcript.
+        let mut transcript = Transcript::new(b"test");
+        let mut poly_comms = vec![];
+        let mut proofs = vec![];
+        let mut zs = vec![];
+        for batch_size in [1, 1, 1, 5] {
+            let (poly_comm, proof, z) = batch_kzg_comm::<Bls12_381, _>(
+                &kzg_comm_scheme,
+                max_degree,
+                batch_size,
+                &mut transcript,
+                &mut rng,
+            );
+            poly_comms.push(poly_comm);
+            proofs.push(proof);
+            zs.push(z);
+        }
+        let proofs = proofs.iter().collect::<Vec<_>>();
 
-        let (poly_comm2, proof2, z2) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
-        let is_ok = kzg_comm_scheme.verify(&poly_comm2, &proof2, &z2);
+        for n in [2, 3, 4] {
+            let mut transcript = Transcript::new(b"test");
+            let is_ok = vk
+                .batch_verify(
+                    &poly_comms[..n],
+                    &proofs[..n],
+                    &zs[..n],
+                    &mut transcript,
+                    &mut rng,
+                )
+                .is_ok();
+            assert!(is_ok);
+        }
+
+        // a single opening is a batch of one polynomial.
+        let (poly_comm, proof, z) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
+        let is_ok = vk.verify(&poly_comm, &proof, &z).is_ok();
+        assert!(is_ok);
+        let mut transcript = Transcript::new(b"test");
+        let is_ok = vk
+            .batch_verify(
+                &[vec![poly_comm]],
+                &[&proof],
+                &[z],
+                &mut transcript,
+                &mut rng,
+            )
+            .is_ok();
         assert!(is_ok);
+    }
 
-        let is_ok = kzg_comm_scheme.batch_verify(
-            &[vec![poly_comm1], vec![poly_comm2]],
-            &[&proof1, &proof2],
-            &[z1, z2],
+    #[test]
+    fn test_batch_kzg_comm_rejects_tampered_openings() {
+        let max_degree = 20;
+        let batch_size = 3;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+        let (poly_comms, proof, z) = batch_kzg_comm(
+            &kzg_comm_scheme,
+            max_degree,
+            batch_size,
+            &mut Transcript::new(b"test"),
             &mut rng,
         );
+        let is_ok = vk
+            .batch_verify(
+                std::slice::from_ref(&poly_comms),
+                &[&proof],
+                &[z],
+                &mut Transcript::new(b"test"),
+                &mut rng,
+            )
+            .is_ok();
         assert!(is_ok);
 
-        let (poly_comm3, proof3, z3) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
-        let is_ok = kzg_comm_scheme.verify(&poly_comm3, &proof3, &z3);
-        assert!(is_ok);
+        // swap the evaluations of the first two polynomials.
+        let mut swapped = proof.clone();
+        swapped.opening_values.swap(0, 1);
+        let res = vk.batch_verify(
+            std::slice::from_ref(&poly_comms),
+            &[&swapped],
+            &[z],
+            &mut Transcript::new(b"test"),
+            &mut rng,
+        );
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
+
+        // shift value from the first polynomial to the second one, the sum is unchanged.
+        let mut shifted = proof.clone();
+        let delta = Fr::rand(&mut rng);
+        shifted.opening_values[0] -= delta;
+        shifted.opening_values[1] += delta;
+        let res = vk.batch_verify(
+            std::slice::from_ref(&poly_comms),
+            &[&shifted],
+            &[z],
+            &mut Transcript::new(b"test"),
+            &mut rng,
+        );
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
+
+        // tamper with a single evaluation.
+        let mut tampered = proof.clone();
+        tampered.opening_values[2] += Fr::one();
+        let res = vk.batch_verify(
+            std::slice::from_ref(&poly_comms),
+            &[&tampered],
+            &[z],
+            &mut Transcript::new(b"test"),
+            &mut rng,
+        );
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
 
-        let is_ok = kzg_comm_scheme.batch_verify(
-            &[vec![poly_comm1], vec![poly_comm2], vec![poly_comm3]],
-            &[&proof1, &proof2, &proof3.clone()],
-            &[z1, z2, z3],
+        // the challenge is derived from the transcript the proof was created on.
+        let res = vk.batch_verify(
+            &[poly_comms],
+            &[&proof],
+            &[z],
+            &mut Transcript::new(b"other"),
             &mut rng,
         );
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
+    }
+
+    #[test]
+    fn test_multi_open() {
+        let max_degree = 20;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+        let polys = (0..4)
+            .map(|_| DensePolynomial::<Fr>::rand(max_degree, &mut rng))
+            .collect::<Vec<_>>();
+        let poly_comms = polys
+            .iter()
+            .map(|poly| kzg_comm_scheme.commit(poly).unwrap())
+            .collect::<Vec<_>>();
+
+        let zeta = Fr::rand(&mut rng);
+        let zeta_omega = Fr::rand(&mut rng);
+        let points = vec![
+            vec![zeta],
+            vec![zeta, zeta_omega],
+            vec![zeta_omega],
+            vec![zeta, zeta_omega, Fr::rand(&mut rng)],
+        ];
+
+        let mut transcript = Transcript::new(b"test");
+        let (proof, evaluations) = kzg_comm_scheme
+            .multi_open(&polys, &poly_comms, &points, &mut transcript)
+            .unwrap();
+        assert_eq!(evaluations[1][1], polys[1].evaluate(&zeta_omega));
+
+        let mut transcript = Transcript::new(b"test");
+        let is_ok = vk
+            .multi_verify(&poly_comms, &points, &evaluations, &proof, &mut transcript)
+            .is_ok();
         assert!(is_ok);
 
-        let (poly_comm4, proof4, z4) =
-            batch_kzg_comm(&kzg_comm_scheme, max_degree, batch_size, &mut rng);
-        let is_ok = kzg_comm_scheme.batch_verify(
-            &[
-                vec![poly_comm1],
-                vec![poly_comm2],
-                vec![poly_comm3],
-                poly_comm4,
-            ],
-            &[&proof1, &proof2.clone(), &proof3.clone(), &proof4],
-            &[z1, z2, z3, z4],
+        // tamper with an evaluation.
+        let mut tampered = evaluations.clone();
+        tampered[3][2] += Fr::one();
+        let mut transcript = Transcript::new(b"test");
+        let res = vk.multi_verify(&poly_comms, &points, &tampered, &proof, &mut transcript);
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
+
+        // open at a point that was not proven.
+        let mut wrong_points = points.clone();
+        wrong_points[0][0] = Fr::rand(&mut rng);
+        let mut transcript = Transcript::new(b"test");
+        let res = vk.multi_verify(
+            &poly_comms,
+            &wrong_points,
+            &evaluations,
+            &proof,
+            &mut transcript,
+        );
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
+
+        // swap two commitments.
+        let mut swapped = poly_comms.clone();
+        swapped.swap(0, 2);
+        let mut transcript = Transcript::new(b"test");
+        let res = vk.multi_verify(&swapped, &points, &evaluations, &proof, &mut transcript);
+        assert!(matches!(res, Err(KzgError::PairingCheckFailed)));
+    }
+
+    #[test]
+    fn test_lagrange_kzg_comm() {
+        let max_degree = 20;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+        let domain = Radix2EvaluationDomain::<Fr>::new(16).unwrap();
+        let basis = srs.lagrange_basis(&domain).unwrap();
+
+        let evals = (0..domain.size())
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let poly = DensePolynomial::from_coefficients_vec(domain.ifft(&evals));
+        let poly_comm = kzg_comm_scheme.commit_evaluations(&basis, &evals).unwrap();
+        assert_eq!(poly_comm, kzg_comm_scheme.commit(&poly).unwrap());
+
+        // z outside and inside the domain.
+        for z in [Fr::rand(&mut rng), domain.element(5)] {
+            let proof = kzg_comm_scheme
+                .prove_evaluations(&basis, &evals, &z)
+                .unwrap();
+            let expect = kzg_comm_scheme.prove(&poly, &z).unwrap();
+            assert_eq!(proof.opening_values, expect.opening_values);
+            assert_eq!(proof.comm_h, expect.comm_h);
+            assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+        }
+
+        let evals = vec![evals, vec![Fr::one(); domain.size()]];
+        let poly_comms = evals
+            .iter()
+            .map(|evals| kzg_comm_scheme.commit_evaluations(&basis, evals).unwrap())
+            .collect::<Vec<_>>();
+        let z = Fr::rand(&mut rng);
+        let mut transcript = Transcript::new(b"test");
+        let proof = kzg_comm_scheme
+            .batch_prove_evaluations(&basis, &evals, &poly_comms, &z, &mut transcript)
+            .unwrap();
+        assert_eq!(proof.opening_values[1], Fr::one());
+        let mut transcript = Transcript::new(b"test");
+        let is_ok = vk
+            .batch_verify(&[poly_comms], &[&proof], &[z], &mut transcript, &mut rng)
+            .is_ok();
+        assert!(is_ok);
+    }
+
+    #[test]
+    fn test_open_all_on_domain() {
+        let max_degree = 20;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+        let poly = DensePolynomial::<Fr>::rand(max_degree, &mut rng);
+        let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
+
+        // the degree is larger than the first domain and smaller than the second one.
+        for size in [8, 32] {
+            let domain = Radix2EvaluationDomain::<Fr>::new(size).unwrap();
+            let proofs = kzg_comm_scheme.open_all_on_domain(&poly, &domain).unwrap();
+            assert_eq!(proofs.len(), size);
+            for (i, proof) in proofs.iter().enumerate() {
+                let z = domain.element(i);
+                let expect = kzg_comm_scheme.prove(&poly, &z).unwrap();
+                assert_eq!(proof.opening_values, expect.opening_values);
+                assert_eq!(proof.comm_h, expect.comm_h);
+                assert!(vk.verify(&poly_comm, proof, &z).is_ok());
+            }
+        }
+
+        let constant = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
+        let domain = Radix2EvaluationDomain::<Fr>::new(4).unwrap();
+        let proofs = kzg_comm_scheme
+            .open_all_on_domain(&constant, &domain)
+            .unwrap();
+        assert!(proofs.iter().all(|proof| proof.comm_h.is_zero()));
+    }
+
+    #[test]
+    fn test_kzg_errors() {
+        let max_degree = 8;
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let (_, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&srs);
+
+        let poly = DensePolynomial::<Fr>::rand(max_degree + 1, &mut rng);
+        assert!(matches!(
+            kzg_comm_scheme.commit(&poly),
+            Err(KzgError::DegreeTooLarge {
+                degree: 9,
+                max_degree: 8
+            })
+        ));
+        let domain = Radix2EvaluationDomain::<Fr>::new(16).unwrap();
+        assert!(matches!(
+            srs.lagrange_basis(&domain),
+            Err(KzgError::DegreeTooLarge { .. })
+        ));
+
+        let (poly_comm, proof, z) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
+        let mut malformed = proof.clone();
+        malformed.opening_values.clear();
+        assert!(matches!(
+            vk.verify(&poly_comm, &malformed, &z),
+            Err(KzgError::MalformedProof)
+        ));
+        assert!(matches!(
+            vk.batch_verify(
+                &[vec![poly_comm]],
+                &[&malformed],
+                &[z],
+                &mut Transcript::new(b"test"),
+                &mut rng
+            ),
+            Err(KzgError::MalformedProof)
+        ));
+        assert!(matches!(
+            vk.batch_verify(
+                &[vec![poly_comm]],
+                &[&proof],
+                &[z, z],
+                &mut Transcript::new(b"test"),
+                &mut rng
+            ),
+            Err(KzgError::LengthMismatch {
+                expected: 1,
+                found: 2
+            })
+        ));
+
+        let polys = vec![DensePolynomial::<Fr>::rand(max_degree, &mut rng)];
+        let points = vec![vec![z, z]];
+        let mut transcript = Transcript::new(b"test");
+        assert!(matches!(
+            kzg_comm_scheme.multi_open(&polys, &[poly_comm], &points, &mut transcript),
+            Err(KzgError::DuplicatePoint)
+        ));
+        assert!(matches!(
+            kzg_comm_scheme.multi_open(&polys, &[], &[vec![z]], &mut transcript),
+            Err(KzgError::LengthMismatch {
+                expected: 1,
+                found: 0
+            })
+        ));
+        let (multi_proof, evaluations) = kzg_comm_scheme
+            .multi_open(&polys, &[poly_comm], &[vec![z]], &mut transcript)
+            .unwrap();
+        assert!(matches!(
+            vk.multi_verify(&[poly_comm], &[vec![z]], &[], &multi_proof, &mut transcript),
+            Err(KzgError::MalformedProof)
+        ));
+        assert!(matches!(
+            vk.multi_verify(&[], &[vec![z]], &evaluations, &multi_proof, &mut transcript),
+            Err(KzgError::LengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_kzg_comm_bn254() {
+        let max_degree = 20;
+        let mut rng = test_rng();
+        let srs = SRS::<Bn254>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+        let (poly_comm, proof, z) = kzg_comm(&kzg_comm_scheme, max_degree, &mut rng);
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+
+        let (poly_comms, batch_proof, batch_z) = batch_kzg_comm(
+            &kzg_comm_scheme,
+            max_degree,
+            3,
+            &mut Transcript::new(b"test"),
             &mut rng,
         );
+        let is_ok = vk
+            .batch_verify(
+                &[poly_comms],
+                &[&batch_proof],
+                &[batch_z],
+                &mut Transcript::new(b"test"),
+                &mut rng,
+            )
+            .is_ok();
+        assert!(is_ok);
+
+        let polys = (0..2)
+            .map(|_| DensePolynomial::<ark_bn254::Fr>::rand(max_degree, &mut rng))
+            .collect::<Vec<_>>();
+        let poly_comms = polys
+            .iter()
+            .map(|poly| kzg_comm_scheme.commit(poly).unwrap())
+            .collect::<Vec<_>>();
+        let points = vec![vec![z], vec![z, batch_z]];
+        let mut transcript = Transcript::new(b"test");
+        let (multi_proof, evaluations) = kzg_comm_scheme
+            .multi_open(&polys, &poly_comms, &points, &mut transcript)
+            .unwrap();
+        let mut transcript = Transcript::new(b"test");
+        let is_ok = vk
+            .multi_verify(
+                &poly_comms,
+                &points,
+                &evaluations,
+                &multi_proof,
+                &mut transcript,
+            )
+            .is_ok();
         assert!(is_ok);
     }
 
@@ -252,10 +1212,10 @@ mod test_kzg {
         }
 
         let poly = DensePolynomial::from_coefficients_vec(coefs);
-        let poly_comm = kzg_comm_scheme.commit(&poly);
+        let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
 
         let z = P::ScalarField::rand(rng);
-        let proof = kzg_comm_scheme.prove(&poly, &z);
+        let proof = kzg_comm_scheme.prove(&poly, &z).unwrap();
 
         (poly_comm, proof, z)
     }
@@ -264,6 +1224,7 @@ mod test_kzg {
         kzg_comm_scheme: &KZGCommitmentScheme<P>,
         max_degree: usize,
         batch_size: usize,
+        transcript: &mut Transcript,
         rng: &mut R,
     ) -> (Vec<P::G1>, KZGCommitmentProof<P::G1>, P::ScalarField) {
         let mut polys = vec![];
@@ -276,15 +1237,16 @@ mod test_kzg {
             }
 
             let poly = DensePolynomial::from_coefficients_vec(coefs);
-            let poly_comm = kzg_comm_scheme.commit(&poly);
+            let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
 
             polys.push(poly);
             poly_comms.push(poly_comm);
         }
 
         let z = P::ScalarField::rand(rng);
-        let challenge = P::ScalarField::rand(rng);
-        let proof = kzg_comm_scheme.batch_prove(&polys, &z, &challenge);
+        let proof = kzg_comm_scheme
+            .batch_prove(&polys, &poly_comms, &z, transcript)
+            .unwrap();
 
         (poly_comms, proof, z)
     }
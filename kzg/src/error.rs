@@ -0,0 +1,54 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum KzgError {
+    /// The polynomial does not fit in the SRS.
+    DegreeTooLarge { degree: usize, max_degree: usize },
+    /// Two inputs that must have the same length do not.
+    LengthMismatch { expected: usize, found: usize },
+    /// The proof does not have the expected shape, e.g. a missing evaluation.
+    MalformedProof,
+    /// The proof is well formed but does not verify.
+    PairingCheckFailed,
+    /// A polynomial is opened twice at the same point.
+    DuplicatePoint,
+    /// The SRS has no powers of τ in G1.
+    EmptySrs,
+    /// The SRS could not be read.
+    Io(io::Error),
+}
+
+impl fmt::Display for KzgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KzgError::DegreeTooLarge { degree, max_degree } => write!(
+                f,
+                "degree {} exceeds the maximum degree {} of the SRS",
+                degree, max_degree
+            ),
+            KzgError::LengthMismatch { expected, found } => {
+                write!(f, "expected {} elements, found {}", expected, found)
+            }
+            KzgError::MalformedProof => write!(f, "malformed proof"),
+            KzgError::PairingCheckFailed => write!(f, "pairing check failed"),
+            KzgError::DuplicatePoint => write!(f, "duplicate opening point"),
+            KzgError::EmptySrs => write!(f, "empty SRS"),
+            KzgError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for KzgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KzgError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for KzgError {
+    fn from(err: io::Error) -> Self {
+        KzgError::Io(err)
+    }
+}
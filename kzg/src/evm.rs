@@ -0,0 +1,165 @@
+//! Byte layout of the Ethereum precompiles `ecAdd`, `ecMul` (EIP-196) and `ecPairing` (EIP-197).
+//!
+//! A field element is a 32-byte big-endian integer, a G1 point is `x | y` and a G2 point is
+//! `x_im | x_re | y_im | y_re`. The point at infinity is encoded as zeros.
+//!
+//! Only fields of at most 256 bits fit in a word, so the encoding is restricted to them at
+//! compile time: BN254 can be encoded, BLS12-381 can not.
+
+use ark_ec::{
+    bn::{Bn, BnConfig},
+    short_weierstrass::{Projective, SWCurveConfig},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{BigInt, BigInteger, Fp2, Fp2Config, PrimeField};
+
+use crate::{
+    commitment::{KZGCommitmentProof, KZGMultiOpeningProof},
+    srs::VerifierKey,
+};
+
+/// A field whose elements fit in a 32-byte word.
+pub trait EvmField: PrimeField<BigInt = BigInt<4>> {}
+
+impl<F: PrimeField<BigInt = BigInt<4>>> EvmField for F {}
+
+pub fn encode_field<F: EvmField>(x: &F) -> [u8; 32] {
+    let bytes = x.into_bigint().to_bytes_be();
+    let mut buf = [0u8; 32];
+    buf[32 - bytes.len()..].copy_from_slice(&bytes);
+    buf
+}
+
+pub fn encode_g1<P: SWCurveConfig>(point: &Projective<P>) -> [u8; 64]
+where
+    P::BaseField: EvmField,
+{
+    let mut buf = [0u8; 64];
+    if let Some((x, y)) = point.into_affine().xy() {
+        buf[..32].copy_from_slice(&encode_field(x));
+        buf[32..].copy_from_slice(&encode_field(y));
+    }
+    buf
+}
+
+pub fn encode_g2<P, Q>(point: &Projective<P>) -> [u8; 128]
+where
+    P: SWCurveConfig<BaseField = Fp2<Q>>,
+    Q: Fp2Config,
+    Q::Fp: EvmField,
+{
+    let mut buf = [0u8; 128];
+    if let Some((x, y)) = point.into_affine().xy() {
+        buf[..32].copy_from_slice(&encode_field(&x.c1));
+        buf[32..64].copy_from_slice(&encode_field(&x.c0));
+        buf[64..96].copy_from_slice(&encode_field(&y.c1));
+        buf[96..].copy_from_slice(&encode_field(&y.c0));
+    }
+    buf
+}
+
+impl<P: SWCurveConfig> KZGCommitmentProof<Projective<P>>
+where
+    P::BaseField: EvmField,
+    P::ScalarField: EvmField,
+{
+    /// comm_h | opening_values[0] | ... | opening_values[n - 1]
+    pub fn to_evm_bytes(&self) -> Vec<u8> {
+        let mut buf = encode_g1(&self.comm_h).to_vec();
+        for opening_value in self.opening_values.iter() {
+            buf.extend_from_slice(&encode_field(opening_value));
+        }
+        buf
+    }
+}
+
+impl<P: SWCurveConfig> KZGMultiOpeningProof<Projective<P>>
+where
+    P::BaseField: EvmField,
+{
+    /// comm_w | comm_w'
+    pub fn to_evm_bytes(&self) -> Vec<u8> {
+        let mut buf = encode_g1(&self.comm_w).to_vec();
+        buf.extend_from_slice(&encode_g1(&self.comm_w_prime));
+        buf
+    }
+}
+
+impl<C: BnConfig> VerifierKey<Bn<C>>
+where
+    C::Fp: EvmField,
+{
+    /// [1]_1 | [1]_2 | [τ]_2
+    pub fn to_evm_bytes(&self) -> Vec<u8> {
+        let mut buf = encode_g1(&self.g1).to_vec();
+        buf.extend_from_slice(&encode_g2(&self.g2));
+        buf.extend_from_slice(&encode_g2(&self.g2_tau));
+        buf
+    }
+}
+
+#[cfg(test)]
+mod test_evm {
+    use super::{encode_g1, encode_g2};
+    use crate::{commitment::KZGCommitmentScheme, srs::SRS};
+    use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+    use ark_ec::Group;
+    use ark_ff::Zero;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_std::{test_rng, UniformRand};
+    use num_bigint::BigUint;
+
+    fn word(decimal: &str) -> Vec<u8> {
+        let bytes = decimal.parse::<BigUint>().unwrap().to_bytes_be();
+        let mut buf = vec![0u8; 32 - bytes.len()];
+        buf.extend_from_slice(&bytes);
+        buf
+    }
+
+    #[test]
+    fn test_encode_generators() {
+        let g1 = encode_g1(&G1Projective::generator());
+        assert_eq!(g1.to_vec(), [word("1"), word("2")].concat());
+
+        // the G2 generator of EIP-197.
+        let g2 = encode_g2(&G2Projective::generator());
+        let expect = [
+            word("11559732032986387107991004021392285783925812861821192530917403151452391805634"),
+            word("10857046999023057135944570762232829481370756359578518086990519993285655852781"),
+            word("4082367875863433681332203403145435568316851327593401208105741076214120093531"),
+            word("8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+        ]
+        .concat();
+        assert_eq!(g2.to_vec(), expect);
+
+        assert_eq!(encode_g1(&G1Projective::zero()), [0u8; 64]);
+        assert_eq!(encode_g2(&G2Projective::zero()), [0u8; 128]);
+    }
+
+    #[test]
+    fn test_encode_kzg_proof() {
+        let max_degree = 16;
+        let mut rng = test_rng();
+        let srs = SRS::<Bn254>::new(max_degree, &mut rng);
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+        let poly = DensePolynomial::<Fr>::rand(max_degree, &mut rng);
+        let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
+        let z = Fr::rand(&mut rng);
+        let proof = kzg_comm_scheme.prove(&poly, &z).unwrap();
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+
+        let bytes = proof.to_evm_bytes();
+        assert_eq!(bytes.len(), 64 + 32);
+        assert_eq!(bytes[..64], encode_g1(&proof.comm_h));
+        assert_eq!(
+            bytes[64..],
+            word(&BigUint::from(proof.opening_values[0]).to_string())
+        );
+
+        let bytes = vk.to_evm_bytes();
+        assert_eq!(bytes.len(), 64 + 128 * 2);
+        assert_eq!(bytes[64..192], encode_g2(&G2Projective::generator()));
+    }
+}
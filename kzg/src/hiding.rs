@@ -0,0 +1,226 @@
+use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_std::rand::Rng;
+use std::ops::{Add, Mul, Sub};
+
+use crate::{error::KzgError, srs::SRS, utils::divide_by_linear};
+
+/// Hiding variant of the KZG commitment scheme, see section 3.3 of the KZG10 paper.
+///
+/// A commitment is `g^{p(τ)} * h^{r(τ)}` where `r(X)` is a random blinding polynomial.
+pub struct HidingKZGCommitmentScheme<'a, P: Pairing>(pub &'a SRS<P>);
+
+#[derive(Debug, Clone)]
+pub struct HidingKZGCommitmentProof<G: CurveGroup> {
+    opening_value: G::ScalarField,
+    blinded_opening_value: G::ScalarField,
+    comm_h: G,
+}
+
+impl<G: CurveGroup> HidingKZGCommitmentProof<G> {
+    pub fn opening_value(&self) -> G::ScalarField {
+        self.opening_value
+    }
+}
+
+/// The part of the SRS needed to verify hiding openings: [1]_1, [h]_1, [1]_2 and [τ]_2.
+#[derive(Debug, Clone)]
+pub struct HidingVerifierKey<P: Pairing> {
+    pub g1: P::G1,
+    pub h: P::G1,
+    pub g2: P::G2,
+    pub g2_tau: P::G2,
+}
+
+impl<'a, P: Pairing> HidingKZGCommitmentScheme<'a, P> {
+    /// The SRS should carry as many powers of `h` as of `g`, which `load_from_ptau` does not.
+    fn check_srs(&self) -> Result<(), KzgError> {
+        if self.0.g1.is_empty() {
+            return Err(KzgError::EmptySrs);
+        }
+        if self.0.h.len() != self.0.g1.len() {
+            return Err(KzgError::LengthMismatch {
+                expected: self.0.g1.len(),
+                found: self.0.h.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_degree(&self, poly: &DensePolynomial<P::ScalarField>) -> Result<(), KzgError> {
+        let max_degree = self.0.g1.len() - 1;
+        if poly.degree() > max_degree {
+            return Err(KzgError::DegreeTooLarge {
+                degree: poly.degree(),
+                max_degree,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn verifier_key(&self) -> Result<HidingVerifierKey<P>, KzgError> {
+        self.check_srs()?;
+        if self.0.g2.len() < 2 {
+            return Err(KzgError::LengthMismatch {
+                expected: 2,
+                found: self.0.g2.len(),
+            });
+        }
+
+        Ok(HidingVerifierKey {
+            g1: self.0.g1[0],
+            h: self.0.h[0],
+            g2: self.0.g2[0],
+            g2_tau: self.0.g2[1],
+        })
+    }
+
+    /// Commits to `poly` with a fresh blinding polynomial of the same degree,
+    /// the blinding polynomial is needed to open the commitment later.
+    pub fn commit<R: Rng>(
+        &self,
+        poly: &DensePolynomial<P::ScalarField>,
+        rng: &mut R,
+    ) -> Result<(P::G1, DensePolynomial<P::ScalarField>), KzgError> {
+        let blinding_poly = DensePolynomial::rand(poly.degree(), rng);
+        let comm = self.commit_with_blinding(poly, &blinding_poly)?;
+
+        Ok((comm, blinding_poly))
+    }
+
+    pub fn commit_with_blinding(
+        &self,
+        poly: &DensePolynomial<P::ScalarField>,
+        blinding_poly: &DensePolynomial<P::ScalarField>,
+    ) -> Result<P::G1, KzgError> {
+        self.check_srs()?;
+        self.check_degree(poly)?;
+        self.check_degree(blinding_poly)?;
+
+        let g_bases = P::G1::normalize_batch(&self.0.g1[..poly.coeffs().len()]);
+        let h_bases = P::G1::normalize_batch(&self.0.h[..blinding_poly.coeffs().len()]);
+        let g_part = P::G1::msm_unchecked(&g_bases, poly.coeffs());
+        let h_part = P::G1::msm_unchecked(&h_bases, blinding_poly.coeffs());
+
+        Ok(g_part.add(h_part))
+    }
+
+    pub fn prove(
+        &self,
+        poly: &DensePolynomial<P::ScalarField>,
+        blinding_poly: &DensePolynomial<P::ScalarField>,
+        z: &P::ScalarField,
+    ) -> Result<HidingKZGCommitmentProof<P::G1>, KzgError> {
+        let (quotient, opening_value) = divide_by_linear(poly, z);
+        let (blinding_quotient, blinded_opening_value) = divide_by_linear(blinding_poly, z);
+
+        let comm_h = self.commit_with_blinding(&quotient, &blinding_quotient)?;
+
+        Ok(HidingKZGCommitmentProof {
+            opening_value,
+            blinded_opening_value,
+            comm_h,
+        })
+    }
+}
+
+impl<P: Pairing> HidingVerifierKey<P> {
+    pub fn verify(
+        &self,
+        poly_comm: &P::G1,
+        proof: &HidingKZGCommitmentProof<P::G1>,
+        z: &P::ScalarField,
+    ) -> Result<(), KzgError> {
+        // e(comm - g^y * h^y', g2) = e(comm_h, g2^{r - z})
+        let left_0 = poly_comm
+            .sub(self.g1.mul(proof.opening_value))
+            .sub(self.h.mul(proof.blinded_opening_value));
+        let left = P::pairing(left_0, self.g2);
+        let right = P::pairing(proof.comm_h, self.g2_tau.sub(self.g2.mul(z)));
+
+        if left != right {
+            return Err(KzgError::PairingCheckFailed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_hiding_kzg {
+    use super::HidingKZGCommitmentScheme;
+    use crate::{error::KzgError, srs::SRS};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::One;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn test_hiding_kzg_comm() {
+        let max_degree = 20;
+        let mut rng = test_rng();
+        let mut srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let hiding_kzg_comm_scheme = HidingKZGCommitmentScheme(&srs);
+        let vk = hiding_kzg_comm_scheme.verifier_key().unwrap();
+
+        let poly = DensePolynomial::<Fr>::rand(max_degree, &mut rng);
+        let (poly_comm, blinding_poly) = hiding_kzg_comm_scheme.commit(&poly, &mut rng).unwrap();
+        let z = Fr::rand(&mut rng);
+        let proof = hiding_kzg_comm_scheme
+            .prove(&poly, &blinding_poly, &z)
+            .unwrap();
+        assert_eq!(proof.opening_value(), poly.evaluate(&z));
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+
+        // committing twice to the same polynomial gives different commitments.
+        let (another_comm, _) = hiding_kzg_comm_scheme.commit(&poly, &mut rng).unwrap();
+        assert_ne!(poly_comm, another_comm);
+
+        let mut tampered = proof.clone();
+        tampered.opening_value += Fr::one();
+        assert!(matches!(
+            vk.verify(&poly_comm, &tampered, &z),
+            Err(KzgError::PairingCheckFailed)
+        ));
+
+        let mut tampered = proof.clone();
+        tampered.blinded_opening_value += Fr::one();
+        assert!(matches!(
+            vk.verify(&poly_comm, &tampered, &z),
+            Err(KzgError::PairingCheckFailed)
+        ));
+
+        srs.update(&mut rng).unwrap();
+        let hiding_kzg_comm_scheme = HidingKZGCommitmentScheme(&srs);
+        let vk = hiding_kzg_comm_scheme.verifier_key().unwrap();
+        let (poly_comm, blinding_poly) = hiding_kzg_comm_scheme.commit(&poly, &mut rng).unwrap();
+        let proof = hiding_kzg_comm_scheme
+            .prove(&poly, &blinding_poly, &z)
+            .unwrap();
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+    }
+
+    #[test]
+    fn test_hiding_kzg_without_h() {
+        let max_degree = 4;
+        let mut rng = test_rng();
+        let mut srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+        let poly = DensePolynomial::<Fr>::rand(max_degree, &mut rng);
+        assert!(matches!(
+            HidingKZGCommitmentScheme(&srs)
+                .commit(&DensePolynomial::rand(max_degree + 1, &mut rng), &mut rng),
+            Err(KzgError::DegreeTooLarge { .. })
+        ));
+
+        // an SRS loaded from a `.ptau` file carries no powers of h.
+        srs.h.clear();
+        let hiding_kzg_comm_scheme = HidingKZGCommitmentScheme(&srs);
+        assert!(matches!(
+            hiding_kzg_comm_scheme.verifier_key(),
+            Err(KzgError::LengthMismatch { found: 0, .. })
+        ));
+        assert!(matches!(
+            hiding_kzg_comm_scheme.commit(&poly, &mut rng),
+            Err(KzgError::LengthMismatch { found: 0, .. })
+        ));
+    }
+}
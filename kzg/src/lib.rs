@@ -1,2 +1,11 @@
+pub mod accumulator;
+pub mod ceremony;
 pub mod commitment;
+pub mod error;
+pub mod evm;
+pub mod hiding;
+pub mod multilinear;
+pub mod ptau;
 pub mod srs;
+pub mod transcripts;
+pub mod utils;
@@ -0,0 +1,293 @@
+//! Multilinear KZG commitments (PST13), https://eprint.iacr.org/2011/587.pdf
+//!
+//! A multilinear polynomial is given by its evaluations over the boolean hypercube, in the
+//! same order as `sumcheck::boolean_hypercube::BooleanHypercube`: the first variable is
+//! the most significant bit of the index.
+
+use ark_ec::{pairing::Pairing, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{One, UniformRand};
+use ark_std::rand::Rng;
+use std::ops::{Add, Mul, Sub};
+
+use crate::error::KzgError;
+
+/// Commitments to the lagrange bases of the hypercube at a secret point τ ∈ F^n.
+///
+/// `g1[k][b] = [eq(b, (τ_k, ..., τ_{n - 1}))]_1`, so `g1[k]` commits to the polynomials
+/// in the last `n - k` variables, and `g2_tau[i] = [τ_i]_2`.
+#[derive(Debug, Clone)]
+pub struct MultilinearSRS<P: Pairing> {
+    pub g1: Vec<Vec<P::G1>>,
+    pub g2: P::G2,
+    pub g2_tau: Vec<P::G2>,
+}
+
+/// The part of the multilinear SRS needed to verify openings.
+#[derive(Debug, Clone)]
+pub struct MultilinearVerifierKey<P: Pairing> {
+    pub g1: P::G1,
+    pub g2: P::G2,
+    pub g2_tau: Vec<P::G2>,
+}
+
+pub struct MultilinearKZGCommitmentScheme<'a, P: Pairing>(pub &'a MultilinearSRS<P>);
+
+/// f(X) - f(z) = \sum_i (X_i - z_i) * q_i(X_{i + 1}, ..., X_{n - 1}), comms_q[i] = [q_i(τ)].
+#[derive(Debug, Clone)]
+pub struct MultilinearKZGProof<G: CurveGroup> {
+    opening_value: G::ScalarField,
+    comms_q: Vec<G>,
+}
+
+impl<G: CurveGroup> MultilinearKZGProof<G> {
+    pub fn opening_value(&self) -> G::ScalarField {
+        self.opening_value
+    }
+}
+
+impl<P: Pairing> MultilinearSRS<P> {
+    pub fn new<R: Rng>(num_vars: usize, rng: &mut R) -> Self {
+        let tau = (0..num_vars)
+            .map(|_| P::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+
+        // eq(b, τ_k..) = eq(b_k, τ_k) * eq(b_{k + 1}.., τ_{k + 1}..), b_k being the most significant bit.
+        let mut eqs = vec![vec![P::ScalarField::one()]];
+        for tau_k in tau.iter().rev() {
+            let last = eqs.last().unwrap();
+            let mut eq = last
+                .iter()
+                .map(|x| x.mul(P::ScalarField::one().sub(tau_k)))
+                .collect::<Vec<_>>();
+            eq.extend(last.iter().map(|x| x.mul(tau_k)));
+            eqs.push(eq);
+        }
+        eqs.reverse();
+
+        let g1_base = P::G1::generator();
+        let g1 = eqs
+            .iter()
+            .map(|eq| eq.iter().map(|x| g1_base.mul(x)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let g2 = P::G2::generator();
+        let g2_tau = tau.iter().map(|x| g2.mul(x)).collect::<Vec<_>>();
+
+        MultilinearSRS { g1, g2, g2_tau }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.g2_tau.len()
+    }
+
+    /// Extracts the keys for polynomials in at most `num_vars` variables, they are bound
+    /// to the last `num_vars` coordinates of τ.
+    pub fn trim(
+        &self,
+        num_vars: usize,
+    ) -> Result<(MultilinearSRS<P>, MultilinearVerifierKey<P>), KzgError> {
+        if num_vars > self.num_vars() {
+            return Err(KzgError::LengthMismatch {
+                expected: self.num_vars(),
+                found: num_vars,
+            });
+        }
+        let skip = self.num_vars() - num_vars;
+
+        let pk = MultilinearSRS {
+            g1: self.g1[skip..].to_vec(),
+            g2: self.g2,
+            g2_tau: self.g2_tau[skip..].to_vec(),
+        };
+        let vk = MultilinearVerifierKey {
+            g1: P::G1::generator(),
+            g2: self.g2,
+            g2_tau: self.g2_tau[skip..].to_vec(),
+        };
+
+        Ok((pk, vk))
+    }
+}
+
+impl<'a, P: Pairing> MultilinearKZGCommitmentScheme<'a, P> {
+    /// Returns the basis of the polynomials with `len` evaluations.
+    fn basis(&self, len: usize) -> Result<&[P::G1], KzgError> {
+        let max_len = self.0.g1[0].len();
+        if !len.is_power_of_two() || len > max_len {
+            return Err(KzgError::LengthMismatch {
+                expected: max_len,
+                found: len,
+            });
+        }
+        let num_vars = len.trailing_zeros() as usize;
+        Ok(&self.0.g1[self.0.num_vars() - num_vars])
+    }
+
+    /// Commits to the multilinear extension of `evals`, `evals.len()` must be a power of two.
+    pub fn commit(&self, evals: &[P::ScalarField]) -> Result<P::G1, KzgError> {
+        let basis = self.basis(evals.len())?;
+        let bases = P::G1::normalize_batch(basis);
+        Ok(P::G1::msm_unchecked(&bases, evals))
+    }
+
+    pub fn prove(
+        &self,
+        evals: &[P::ScalarField],
+        point: &[P::ScalarField],
+    ) -> Result<MultilinearKZGProof<P::G1>, KzgError> {
+        self.basis(evals.len())?;
+        let num_vars = evals.len().trailing_zeros() as usize;
+        if point.len() != num_vars {
+            return Err(KzgError::LengthMismatch {
+                expected: num_vars,
+                found: point.len(),
+            });
+        }
+
+        // fix the variables one by one, f_{i + 1}(X_{i + 1}..) = f_i(z_i, X_{i + 1}..) and
+        // q_i(X_{i + 1}..) = f_i(1, X_{i + 1}..) - f_i(0, X_{i + 1}..).
+        let mut f = evals.to_vec();
+        let mut comms_q = vec![];
+        for z_i in point.iter() {
+            let half = f.len() / 2;
+            let (low, high) = f.split_at(half);
+            let q = high
+                .iter()
+                .zip(low.iter())
+                .map(|(h, l)| h.sub(l))
+                .collect::<Vec<_>>();
+            comms_q.push(self.commit(&q)?);
+            f = low
+                .iter()
+                .zip(q.iter())
+                .map(|(l, q)| l.add(q.mul(z_i)))
+                .collect();
+        }
+
+        Ok(MultilinearKZGProof {
+            opening_value: f[0],
+            comms_q,
+        })
+    }
+}
+
+impl<P: Pairing> MultilinearVerifierKey<P> {
+    /// e(comm - [f(z)]_1, [1]_2) = \prod_i e(comms_q[i], [τ_i - z_i]_2)
+    pub fn verify(
+        &self,
+        poly_comm: &P::G1,
+        point: &[P::ScalarField],
+        proof: &MultilinearKZGProof<P::G1>,
+    ) -> Result<(), KzgError> {
+        if point.len() > self.g2_tau.len() {
+            return Err(KzgError::LengthMismatch {
+                expected: self.g2_tau.len(),
+                found: point.len(),
+            });
+        }
+        if proof.comms_q.len() != point.len() {
+            return Err(KzgError::MalformedProof);
+        }
+
+        let g2_tau = &self.g2_tau[self.g2_tau.len() - point.len()..];
+        let left = P::pairing(poly_comm.sub(self.g1.mul(proof.opening_value)), self.g2);
+        let right = P::multi_pairing(
+            proof.comms_q.iter().copied(),
+            g2_tau
+                .iter()
+                .zip(point.iter())
+                .map(|(tau, z)| tau.sub(self.g2.mul(z))),
+        );
+
+        if left != right {
+            return Err(KzgError::PairingCheckFailed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_multilinear_kzg {
+    use super::{MultilinearKZGCommitmentScheme, MultilinearSRS};
+    use crate::error::KzgError;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::{One, Zero};
+    use ark_std::{test_rng, UniformRand};
+
+    /// \sum_b f(b) * eq(b, z), b_0 being the most significant bit.
+    fn evaluate(evals: &[Fr], point: &[Fr]) -> Fr {
+        let n = point.len();
+        evals
+            .iter()
+            .enumerate()
+            .map(|(b, f)| {
+                let eq = point
+                    .iter()
+                    .enumerate()
+                    .map(|(i, z)| match (b >> (n - 1 - i)) & 1 {
+                        1 => *z,
+                        _ => Fr::one() - z,
+                    })
+                    .product::<Fr>();
+                *f * eq
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_multilinear_kzg() {
+        let num_vars = 5;
+        let mut rng = test_rng();
+        let srs = MultilinearSRS::<Bls12_381>::new(num_vars, &mut rng);
+        let (pk, vk) = srs.trim(num_vars).unwrap();
+        let scheme = MultilinearKZGCommitmentScheme(&pk);
+
+        let evals = (0..1 << num_vars)
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let comm = scheme.commit(&evals).unwrap();
+        let point = (0..num_vars)
+            .map(|_| Fr::rand(&mut rng))
+            .collect::<Vec<_>>();
+        let proof = scheme.prove(&evals, &point).unwrap();
+        assert_eq!(proof.opening_value(), evaluate(&evals, &point));
+        assert!(vk.verify(&comm, &point, &proof).is_ok());
+
+        // on the hypercube the opening is the evaluation itself, 0b10110 = 22.
+        let vertex = [1u64, 0, 1, 1, 0].map(Fr::from);
+        let proof = scheme.prove(&evals, &vertex).unwrap();
+        assert_eq!(proof.opening_value(), evals[22]);
+        assert!(vk.verify(&comm, &vertex, &proof).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.opening_value += Fr::one();
+        assert!(matches!(
+            vk.verify(&comm, &vertex, &tampered),
+            Err(KzgError::PairingCheckFailed)
+        ));
+        let mut tampered = proof.clone();
+        tampered.comms_q.pop();
+        assert!(matches!(
+            vk.verify(&comm, &vertex, &tampered),
+            Err(KzgError::MalformedProof)
+        ));
+
+        // a polynomial in fewer variables uses the last coordinates of τ.
+        let (pk, vk) = srs.trim(3).unwrap();
+        let scheme = MultilinearKZGCommitmentScheme(&pk);
+        let evals = (0..1 << 2).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let comm = scheme.commit(&evals).unwrap();
+        let point = [Fr::rand(&mut rng), Fr::zero()];
+        let proof = scheme.prove(&evals, &point).unwrap();
+        assert_eq!(proof.opening_value(), evaluate(&evals, &point));
+        assert!(vk.verify(&comm, &point, &proof).is_ok());
+
+        assert!(matches!(
+            scheme.commit(&[Fr::one(); 3]),
+            Err(KzgError::LengthMismatch { .. })
+        ));
+        assert!(matches!(
+            scheme.prove(&evals, &[Fr::one()]),
+            Err(KzgError::LengthMismatch { .. })
+        ));
+    }
+}
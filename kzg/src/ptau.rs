@@ -0,0 +1,184 @@
+//! Reader of the `.ptau` files produced by snarkjs and the perpetual powers of tau ceremony.
+//!
+//! file:      "ptau" | version: u32 | num_sections: u32 | sections
+//! section:   type: u32 | size: u64 | data
+//! section 1: n8: u32 | q: [u8; n8] | power: u32 | ceremony_power: u32
+//! section 2: tau^i * G1, for i in 0..2^(power + 1) - 1
+//! section 3: tau^i * G2, for i in 0..2^power
+//!
+//! All integers are little-endian, and the coordinates of the points are stored
+//! little-endian in montgomery form, G2 coordinates as (c0, c1).
+
+use ark_ec::{
+    bls12::{Bls12, Bls12Config},
+    bn::{Bn, BnConfig},
+    pairing::Pairing,
+    short_weierstrass::Affine,
+};
+use ark_ff::{BigInteger, Field, Fp2, PrimeField};
+use ark_serialize::CanonicalDeserialize;
+use std::{
+    io::{Error, ErrorKind, Read, Result, Seek, SeekFrom},
+    ops::Mul,
+};
+
+const MAGIC: &[u8; 4] = b"ptau";
+const HEADER_SECTION: u32 = 1;
+const TAU_G1_SECTION: u32 = 2;
+const TAU_G2_SECTION: u32 = 3;
+
+/// Pairings whose points can be rebuilt from the coordinates stored in a `.ptau` file.
+pub trait PtauPairing: Pairing {
+    /// Returns `None` if the point is not on the curve or not in the prime order subgroup.
+    fn g1_from_coordinates(x: Self::BaseField, y: Self::BaseField) -> Option<Self::G1Affine>;
+
+    /// Returns `None` if the point is not on the curve or not in the prime order subgroup.
+    fn g2_from_coordinates(
+        x: (Self::BaseField, Self::BaseField),
+        y: (Self::BaseField, Self::BaseField),
+    ) -> Option<Self::G2Affine>;
+}
+
+impl<C: Bls12Config> PtauPairing for Bls12<C> {
+    fn g1_from_coordinates(x: C::Fp, y: C::Fp) -> Option<Self::G1Affine> {
+        let point = Affine::<C::G1Config>::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+
+    fn g2_from_coordinates(x: (C::Fp, C::Fp), y: (C::Fp, C::Fp)) -> Option<Self::G2Affine> {
+        let x = Fp2::<C::Fp2Config>::new(x.0, x.1);
+        let y = Fp2::<C::Fp2Config>::new(y.0, y.1);
+        let point = Affine::<C::G2Config>::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+}
+
+impl<C: BnConfig> PtauPairing for Bn<C> {
+    fn g1_from_coordinates(x: C::Fp, y: C::Fp) -> Option<Self::G1Affine> {
+        let point = Affine::<C::G1Config>::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+
+    fn g2_from_coordinates(x: (C::Fp, C::Fp), y: (C::Fp, C::Fp)) -> Option<Self::G2Affine> {
+        let x = Fp2::<C::Fp2Config>::new(x.0, x.1);
+        let y = Fp2::<C::Fp2Config>::new(y.0, y.1);
+        let point = Affine::<C::G2Config>::new_unchecked(x, y);
+        (point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+    }
+}
+
+pub(crate) struct PowersOfTau<P: Pairing> {
+    pub g1: Vec<P::G1Affine>,
+    pub g2: Vec<P::G2Affine>,
+}
+
+/// Reads the first `num_g1` powers of tau in G1 and the first `num_g2` powers of tau in G2.
+pub(crate) fn read_ptau<P: PtauPairing, R: Read + Seek>(
+    reader: &mut R,
+    num_g1: usize,
+    num_g2: usize,
+) -> Result<PowersOfTau<P>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a ptau file"));
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+
+    // section type => (position, size)
+    let mut sections = vec![];
+    for _ in 0..num_sections {
+        let section_type = read_u32(reader)?;
+        let section_size = read_u64(reader)?;
+        let position = reader.stream_position()?;
+        sections.push((section_type, position, section_size));
+        reader.seek(SeekFrom::Current(section_size as i64))?;
+    }
+    let seek_section = |reader: &mut R, section_type: u32| -> Result<u64> {
+        let (_, position, size) = sections
+            .iter()
+            .find(|(t, _, _)| *t == section_type)
+            .ok_or_else(|| invalid_data("missing section"))?;
+        reader.seek(SeekFrom::Start(*position))?;
+        Ok(*size)
+    };
+
+    // 1. header
+    seek_section(reader, HEADER_SECTION)?;
+    // n8 comes from the file, it is checked before sizing any buffer with it.
+    let modulus = P::BaseField::MODULUS.to_bytes_le();
+    let n8 = read_u32(reader)? as usize;
+    if n8 != modulus.len() {
+        return Err(invalid_data("the ptau file is defined over another curve"));
+    }
+    let mut q = vec![0u8; n8];
+    reader.read_exact(&mut q)?;
+    if q != modulus {
+        return Err(invalid_data("the ptau file is defined over another curve"));
+    }
+    let power = read_u32(reader)?;
+    if power >= usize::BITS - 1 {
+        return Err(invalid_data("invalid power"));
+    }
+    if num_g1 > (1 << (power + 1)) - 1 || num_g2 > 1 << power {
+        return Err(invalid_data("the ptau file does not contain enough powers"));
+    }
+
+    // R^{-1}, where R = 2^{8 * n8} is the montgomery radix used by the file.
+    let r_inv = P::BaseField::from(2u64)
+        .pow([8 * n8 as u64])
+        .inverse()
+        .unwrap();
+    let read_fq = |reader: &mut R| -> Result<P::BaseField> {
+        let mut buf = vec![0u8; n8];
+        reader.read_exact(&mut buf)?;
+        let x = P::BaseField::deserialize_uncompressed(&buf[..])
+            .map_err(|_| invalid_data("invalid field element"))?;
+        Ok(x.mul(&r_inv))
+    };
+
+    // 2. tau^i * G1
+    let size = seek_section(reader, TAU_G1_SECTION)?;
+    if (size as u128) < num_g1 as u128 * 2 * n8 as u128 {
+        return Err(invalid_data("truncated tau_g1 section"));
+    }
+    let mut g1 = Vec::with_capacity(num_g1);
+    for _ in 0..num_g1 {
+        let x = read_fq(reader)?;
+        let y = read_fq(reader)?;
+        let point = P::g1_from_coordinates(x, y).ok_or_else(|| invalid_data("invalid G1 point"))?;
+        g1.push(point);
+    }
+
+    // 3. tau^i * G2
+    let size = seek_section(reader, TAU_G2_SECTION)?;
+    if (size as u128) < num_g2 as u128 * 4 * n8 as u128 {
+        return Err(invalid_data("truncated tau_g2 section"));
+    }
+    let mut g2 = Vec::with_capacity(num_g2);
+    for _ in 0..num_g2 {
+        let x = (read_fq(reader)?, read_fq(reader)?);
+        let y = (read_fq(reader)?, read_fq(reader)?);
+        let point = P::g2_from_coordinates(x, y).ok_or_else(|| invalid_data("invalid G2 point"))?;
+        g2.push(point);
+    }
+
+    Ok(PowersOfTau { g1, g2 })
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
@@ -1,17 +1,56 @@
 use ark_ec::{pairing::Pairing, Group};
 use ark_ff::{One, UniformRand};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_std::rand::Rng;
-use std::ops::{Mul, MulAssign};
+use std::{
+    io::{self, Read, Seek},
+    ops::{Mul, MulAssign},
+};
+
+use crate::{
+    error::KzgError,
+    ptau::{read_ptau, PtauPairing},
+};
 
 /// Structured Reference String, defined over BLS12-381 curve.
 #[derive(Debug, Clone)]
 pub struct SRS<P: Pairing> {
     pub g1: Vec<P::G1>,
     pub g2: Vec<P::G2>,
+    /// Powers of the secret under an independent generator `h`, used by hiding commitments.
+    pub h: Vec<P::G1>,
+}
+
+/// The part of the SRS needed to commit and open, trimmed to a maximum degree.
+pub type ProverKey<P> = SRS<P>;
+
+/// The part of the SRS needed to verify openings: [1]_1, [1]_2 and [τ]_2.
+#[derive(Debug, Clone)]
+pub struct VerifierKey<P: Pairing> {
+    pub g1: P::G1,
+    pub g2: P::G2,
+    pub g2_tau: P::G2,
+}
+
+/// Commitments to the lagrange polynomials of a domain, g1[i] = [L_i(τ)].
+#[derive(Debug, Clone)]
+pub struct LagrangeBasis<P: Pairing> {
+    pub domain: Radix2EvaluationDomain<P::ScalarField>,
+    pub g1: Vec<P::G1>,
 }
 
 impl<P: Pairing> SRS<P> {
     pub fn new<R: Rng>(max_degree: usize, rng: &mut R) -> Self {
+        Self::new_with_g2_powers(max_degree, 1, rng)
+    }
+
+    /// Same as `new`, but with the powers of τ in G2 up to `max_g2_degree`, which are
+    /// needed to commit to polynomials in G2.
+    pub fn new_with_g2_powers<R: Rng>(
+        max_degree: usize,
+        max_g2_degree: usize,
+        rng: &mut R,
+    ) -> Self {
         let r = P::ScalarField::rand(rng);
 
         let mut g1 = Vec::new();
@@ -21,23 +60,152 @@ impl<P: Pairing> SRS<P> {
             g1.push(ele)
         }
 
-        let g2 = vec![P::G2::generator(), P::G2::generator().mul(r)];
+        let mut g2 = Vec::new();
+        g2.push(P::G2::generator());
+        for i in 0..max_g2_degree.max(1) {
+            let ele = g2[i].mul(&r);
+            g2.push(ele)
+        }
+
+        // nobody should know the discrete logarithm of h with respect to g.
+        let s = P::ScalarField::rand(rng);
+        let mut h = Vec::new();
+        h.push(P::G1::generator().mul(s));
+        for i in 0..max_degree {
+            let ele = h[i].mul(&r);
+            h.push(ele)
+        }
 
-        SRS { g1, g2 }
+        SRS { g1, g2, h }
     }
 
     /// Update SRS.
-    pub fn update<R: Rng>(&mut self, rng: &mut R) {
-        assert!(self.g2.len() == 2);
+    pub fn update<R: Rng>(&mut self, rng: &mut R) -> Result<(), KzgError> {
+        if self.g2.len() < 2 {
+            return Err(KzgError::LengthMismatch {
+                expected: 2,
+                found: self.g2.len(),
+            });
+        }
         let r = P::ScalarField::rand(rng);
+        self.update_with_secret(&r);
+        Ok(())
+    }
+
+    /// Multiplies the secret of the SRS by `r`.
+    pub(crate) fn update_with_secret(&mut self, r: &P::ScalarField) {
+        assert!(self.g2.len() >= 2);
 
         let mut r_pow = P::ScalarField::one();
         for x in self.g1.iter_mut().skip(1) {
-            r_pow.mul_assign(&r);
+            r_pow.mul_assign(r);
             x.mul_assign(r_pow);
         }
 
-        self.g2[1].mul_assign(r);
+        let mut r_pow = P::ScalarField::one();
+        for x in self.h.iter_mut().skip(1) {
+            r_pow.mul_assign(r);
+            x.mul_assign(r_pow);
+        }
+
+        let mut r_pow = P::ScalarField::one();
+        for x in self.g2.iter_mut().skip(1) {
+            r_pow.mul_assign(r);
+            x.mul_assign(r_pow);
+        }
+    }
+
+    /// Extracts the keys to commit to polynomials of degree at most `max_degree` and
+    /// to verify their openings.
+    pub fn trim(&self, max_degree: usize) -> Result<(ProverKey<P>, VerifierKey<P>), KzgError> {
+        if self.g1.is_empty() {
+            return Err(KzgError::EmptySrs);
+        }
+        if max_degree >= self.g1.len() {
+            return Err(KzgError::DegreeTooLarge {
+                degree: max_degree,
+                max_degree: self.g1.len() - 1,
+            });
+        }
+        if self.g2.len() < 2 {
+            return Err(KzgError::LengthMismatch {
+                expected: 2,
+                found: self.g2.len(),
+            });
+        }
+
+        let pk = SRS {
+            g1: self.g1[..=max_degree].to_vec(),
+            g2: self.g2[..2].to_vec(),
+            h: self.h.iter().take(max_degree + 1).copied().collect(),
+        };
+        let vk = VerifierKey {
+            g1: self.g1[0],
+            g2: self.g2[0],
+            g2_tau: self.g2[1],
+        };
+
+        Ok((pk, vk))
+    }
+
+    /// Converts the SRS into lagrange form, [L_i(τ)] = 1/n * \sum_j ω^{-ij} * [τ^j]
+    /// is the inverse FFT of the first n powers of τ.
+    pub fn lagrange_basis(
+        &self,
+        domain: &Radix2EvaluationDomain<P::ScalarField>,
+    ) -> Result<LagrangeBasis<P>, KzgError> {
+        if self.g1.is_empty() {
+            return Err(KzgError::EmptySrs);
+        }
+        if domain.size() > self.g1.len() {
+            return Err(KzgError::DegreeTooLarge {
+                degree: domain.size() - 1,
+                max_degree: self.g1.len() - 1,
+            });
+        }
+        let g1 = domain.ifft(&self.g1[..domain.size()]);
+
+        Ok(LagrangeBasis {
+            domain: *domain,
+            g1,
+        })
+    }
+
+    /// Loads the SRS from a `.ptau` file of snarkjs or the perpetual powers of tau ceremony,
+    /// keeping the powers up to `max_degree`.
+    ///
+    /// The file carries no powers of `h`, so the loaded SRS can not be used for hiding commitments.
+    pub fn load_from_ptau<Rd: Read + Seek, R: Rng>(
+        reader: &mut Rd,
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self, KzgError>
+    where
+        P: PtauPairing,
+    {
+        let num_g1 = max_degree.checked_add(1).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "max_degree is too large")
+        })?;
+        let powers = read_ptau::<P, _>(reader, num_g1, 2)?;
+        let g1 = powers.g1.into_iter().map(P::G1::from).collect::<Vec<_>>();
+        let g2 = powers.g2.into_iter().map(P::G2::from).collect::<Vec<_>>();
+
+        // e(g1[i + 1], g2[0]) = e(g1[i], g2[1]) for every i, checked at once with
+        // a random linear combination.
+        let r = P::ScalarField::rand(rng);
+        let mut r_pow = P::ScalarField::one();
+        let mut left = P::G1::default();
+        let mut right = P::G1::default();
+        for i in 0..max_degree {
+            left += g1[i + 1].mul(r_pow);
+            right += g1[i].mul(r_pow);
+            r_pow.mul_assign(&r);
+        }
+        if P::pairing(left, g2[0]) != P::pairing(right, g2[1]) {
+            return Err(KzgError::PairingCheckFailed);
+        }
+
+        Ok(SRS { g1, g2, h: vec![] })
     }
 
     // /// The public setup parameters come from https://github.com/findora-crypto/export-setup-parameters.
@@ -48,3 +216,215 @@ impl<P: Pairing> SRS<P> {
     //     SRS { g1, g2 }
     // }
 }
+
+#[cfg(test)]
+mod test_srs {
+    use super::SRS;
+    use crate::{commitment::KZGCommitmentScheme, error::KzgError};
+    use ark_bls12_381::{Bls12_381, Fq, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+    use ark_ec::{pairing::Pairing, Group};
+    use ark_ff::{BigInteger, Field, PrimeField};
+    use ark_poly::{
+        univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
+    };
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::{test_rng, UniformRand};
+    use std::{io::Cursor, ops::Mul};
+
+    const PTAU_FIXTURE: &[u8] = include_bytes!("../fixtures/powers_of_tau_bls12_381_3.ptau");
+
+    /// Writes a `.ptau` file of 2^power powers of `tau`, the same way as snarkjs.
+    fn write_ptau(power: u32, tau: Fr) -> Vec<u8> {
+        let n8 = 48;
+        // montgomery form, R = 2^{8 * n8}
+        let r = Fq::from(2u64).pow([8 * n8 as u64]);
+        let write_fq = |buf: &mut Vec<u8>, x: &Fq| {
+            x.mul(&r).serialize_uncompressed(buf).unwrap();
+        };
+
+        let mut header = vec![];
+        header.extend_from_slice(&(n8 as u32).to_le_bytes());
+        header.extend_from_slice(&Fq::MODULUS.to_bytes_le());
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes());
+
+        let mut tau_g1 = vec![];
+        let mut tau_pow = Fr::from(1u64);
+        for _ in 0..(1 << (power + 1)) - 1 {
+            let point = G1Affine::from(G1Projective::generator().mul(tau_pow));
+            write_fq(&mut tau_g1, &point.x);
+            write_fq(&mut tau_g1, &point.y);
+            tau_pow *= tau;
+        }
+
+        let mut tau_g2 = vec![];
+        let mut tau_pow = Fr::from(1u64);
+        for _ in 0..1 << power {
+            let point = G2Affine::from(G2Projective::generator().mul(tau_pow));
+            write_fq(&mut tau_g2, &point.x.c0);
+            write_fq(&mut tau_g2, &point.x.c1);
+            write_fq(&mut tau_g2, &point.y.c0);
+            write_fq(&mut tau_g2, &point.y.c1);
+            tau_pow *= tau;
+        }
+
+        let mut buf = b"ptau".to_vec();
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        for (section_type, section) in [(1u32, header), (2, tau_g1), (3, tau_g2)] {
+            buf.extend_from_slice(&section_type.to_le_bytes());
+            buf.extend_from_slice(&(section.len() as u64).to_le_bytes());
+            buf.extend_from_slice(&section);
+        }
+        buf
+    }
+
+    fn fixture_tau() -> Fr {
+        Fr::from(0x5eed_u64)
+    }
+
+    #[ignore = "regenerates the ptau fixture"]
+    #[test]
+    fn generate_ptau_fixture() {
+        let buf = write_ptau(3, fixture_tau());
+        std::fs::write("fixtures/powers_of_tau_bls12_381_3.ptau", buf).unwrap();
+    }
+
+    #[test]
+    fn test_load_from_ptau() {
+        assert_eq!(PTAU_FIXTURE, write_ptau(3, fixture_tau()));
+
+        let max_degree = 10;
+        let mut rng = test_rng();
+        let srs =
+            SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(PTAU_FIXTURE), max_degree, &mut rng)
+                .unwrap();
+        assert_eq!(srs.g1.len(), max_degree + 1);
+        assert_eq!(
+            srs.g1[3],
+            G1Projective::generator().mul(fixture_tau().pow([3]))
+        );
+        assert_eq!(srs.g2[1], G2Projective::generator().mul(fixture_tau()));
+
+        let (pk, vk) = srs.trim(max_degree).unwrap();
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+        let poly = DensePolynomial::<Fr>::rand(max_degree, &mut rng);
+        let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
+        let z = Fr::rand(&mut rng);
+        let proof = kzg_comm_scheme.prove(&poly, &z).unwrap();
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+
+        // the fixture contains 2^4 - 1 powers in G1.
+        assert!(matches!(
+            SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(PTAU_FIXTURE), 15, &mut rng),
+            Err(KzgError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_trim() {
+        let mut rng = test_rng();
+        let srs = SRS::<Bls12_381>::new(16, &mut rng);
+        let (pk, vk) = srs.trim(4).unwrap();
+        assert_eq!(pk.g1, srs.g1[..5]);
+        assert_eq!(pk.h, srs.h[..5]);
+        assert_eq!(vk.g2_tau, srs.g2[1]);
+
+        let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+        let poly = DensePolynomial::<Fr>::rand(5, &mut rng);
+        assert!(matches!(
+            kzg_comm_scheme.commit(&poly),
+            Err(KzgError::DegreeTooLarge { .. })
+        ));
+        let poly = DensePolynomial::<Fr>::rand(4, &mut rng);
+        let poly_comm = kzg_comm_scheme.commit(&poly).unwrap();
+        let z = Fr::rand(&mut rng);
+        let proof = kzg_comm_scheme.prove(&poly, &z).unwrap();
+        assert!(vk.verify(&poly_comm, &proof, &z).is_ok());
+
+        assert!(matches!(srs.trim(17), Err(KzgError::DegreeTooLarge { .. })));
+
+        let empty = SRS::<Bls12_381> {
+            g1: vec![],
+            g2: srs.g2.clone(),
+            h: vec![],
+        };
+        assert!(matches!(empty.trim(0), Err(KzgError::EmptySrs)));
+        let domain = Radix2EvaluationDomain::<Fr>::new(4).unwrap();
+        assert!(matches!(
+            empty.lagrange_basis(&domain),
+            Err(KzgError::EmptySrs)
+        ));
+        assert!(matches!(
+            KZGCommitmentScheme(&empty).commit(&poly),
+            Err(KzgError::EmptySrs)
+        ));
+    }
+
+    #[test]
+    fn test_g2_powers() {
+        let mut rng = test_rng();
+        let mut srs = SRS::<Bls12_381>::new_with_g2_powers(4, 8, &mut rng);
+        assert_eq!(srs.g2.len(), 9);
+        srs.update(&mut rng).unwrap();
+
+        // e([τ]_1, [τ^i]_2) = e([1]_1, [τ^{i + 1}]_2) after the update as well.
+        for i in 0..8 {
+            assert_eq!(
+                Bls12_381::pairing(srs.g1[1], srs.g2[i]),
+                Bls12_381::pairing(srs.g1[0], srs.g2[i + 1])
+            );
+        }
+        let (_, vk) = srs.trim(4).unwrap();
+        assert_eq!(vk.g2_tau, srs.g2[1]);
+    }
+
+    #[test]
+    fn test_load_from_invalid_ptau() {
+        let mut rng = test_rng();
+        let header_len = 4 + 4 + 4 + (4 + 8) + (4 + 48 + 4 + 4) + (4 + 8);
+
+        // a point which is not on the curve.
+        let mut buf = PTAU_FIXTURE.to_vec();
+        buf[header_len + 96 + 1] ^= 1;
+        assert!(matches!(
+            SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(buf), 4, &mut rng),
+            Err(KzgError::Io(_))
+        ));
+
+        // valid points that are not consecutive powers.
+        let mut buf = PTAU_FIXTURE.to_vec();
+        let (first, second) = (header_len + 2 * 96, header_len + 3 * 96);
+        let point = buf[first..second].to_vec();
+        buf.copy_within(second..second + 96, first);
+        buf[second..second + 96].copy_from_slice(&point);
+        assert!(matches!(
+            SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(buf), 4, &mut rng),
+            Err(KzgError::PairingCheckFailed)
+        ));
+
+        // not a ptau file.
+        let mut buf = PTAU_FIXTURE.to_vec();
+        buf[0] = b'x';
+        assert!(matches!(
+            SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(buf), 4, &mut rng),
+            Err(KzgError::Io(_))
+        ));
+
+        // a field size that is not the one of Fq, up to a huge one.
+        let n8_offset = 4 + 4 + 4 + (4 + 8);
+        for n8 in [32u32, 49, u32::MAX] {
+            let mut buf = PTAU_FIXTURE.to_vec();
+            buf[n8_offset..n8_offset + 4].copy_from_slice(&n8.to_le_bytes());
+            assert!(matches!(
+                SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(buf), 4, &mut rng),
+                Err(KzgError::Io(_))
+            ));
+        }
+
+        assert!(matches!(
+            SRS::<Bls12_381>::load_from_ptau(&mut Cursor::new(PTAU_FIXTURE), usize::MAX, &mut rng),
+            Err(KzgError::Io(_))
+        ));
+    }
+}
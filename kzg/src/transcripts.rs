@@ -1,22 +1,33 @@
 use ark_ec::CurveGroup;
 use ark_ff::UniformRand;
+use ark_serialize::CanonicalSerialize;
 use ark_std::rand::SeedableRng;
 use merlin::Transcript;
 use rand_chacha::ChaChaRng;
 
 pub trait GlobalTranscript<G: CurveGroup> {
-    fn append_commitent(&mut self, comm: &G);
+    fn append_commitment(&mut self, comm: &G);
+
+    fn append_scalars(&mut self, scalars: &[G::ScalarField]);
 
     fn get_challenge(&mut self, label: &'static [u8]) -> G::ScalarField;
 }
 
 impl<G: CurveGroup> GlobalTranscript<G> for Transcript {
-    fn append_commitent(&mut self, comm: &G) {
+    fn append_commitment(&mut self, comm: &G) {
         let mut buf = Vec::new();
         comm.serialize_uncompressed(&mut buf).unwrap();
         self.append_message(b"append commitment", &buf)
     }
 
+    fn append_scalars(&mut self, scalars: &[G::ScalarField]) {
+        for scalar in scalars {
+            let mut buf = Vec::new();
+            scalar.serialize_uncompressed(&mut buf).unwrap();
+            self.append_message(b"append scalar", &buf)
+        }
+    }
+
     fn get_challenge(&mut self, label: &'static [u8]) -> G::ScalarField {
         let mut buf = [0u8; 32];
         self.challenge_bytes(label, &mut buf);
@@ -0,0 +1,154 @@
+use ark_ff::{batch_inversion, FftField, Field, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
+};
+
+/// Divides `poly` by (X - z) with a synthetic division, returns the quotient and the remainder p(z).
+pub fn divide_by_linear<F: Field>(poly: &DensePolynomial<F>, z: &F) -> (DensePolynomial<F>, F) {
+    let coeffs = poly.coeffs();
+    if coeffs.is_empty() {
+        return (DensePolynomial::zero(), F::zero());
+    }
+
+    // q_{i - 1} = c_i + z * q_i, from the leading coefficient down.
+    let mut quotient = vec![F::zero(); coeffs.len() - 1];
+    let mut remainder = coeffs[coeffs.len() - 1];
+    for i in (0..coeffs.len() - 1).rev() {
+        quotient[i] = remainder;
+        remainder.mul_assign(z);
+        remainder.add_assign(coeffs[i]);
+    }
+
+    (DensePolynomial::from_coefficients_vec(quotient), remainder)
+}
+
+/// Divides `dividend` by Z_S(X) = \prod (X - s) in evaluation form, over a coset of a radix-2
+/// domain where Z_S does not vanish. The division must be exact.
+///
+/// Returns `None` if no such coset is found.
+pub fn divide_by_vanishing_poly<F: FftField>(
+    dividend: &DensePolynomial<F>,
+    points: &[F],
+) -> Option<DensePolynomial<F>> {
+    if dividend.is_zero() {
+        return Some(DensePolynomial::zero());
+    }
+
+    let domain = Radix2EvaluationDomain::<F>::new(dividend.coeffs().len())?;
+    let mut offset = F::GENERATOR;
+    for _ in 0..points.len() + 1 {
+        let coset = domain.get_coset(offset)?;
+        let mut divisor_evals = coset
+            .elements()
+            .map(|x| points.iter().map(|s| x.sub(s)).product::<F>())
+            .collect::<Vec<_>>();
+        if divisor_evals.iter().any(|x| x.is_zero()) {
+            // a point of S lies in the coset, there are at most |S| such cosets.
+            offset.mul_assign(F::GENERATOR);
+            continue;
+        }
+        batch_inversion(&mut divisor_evals);
+
+        let evals = coset.fft(dividend.coeffs());
+        let quotient_evals = evals
+            .iter()
+            .zip(divisor_evals.iter())
+            .map(|(e, d)| e.mul(d))
+            .collect::<Vec<_>>();
+
+        return Some(DensePolynomial::from_coefficients_vec(
+            coset.ifft(&quotient_evals),
+        ));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test_utils {
+    use super::{divide_by_linear, divide_by_vanishing_poly};
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+    use ark_poly::{
+        univariate::{DenseOrSparsePolynomial, DensePolynomial},
+        DenseUVPolynomial, Polynomial,
+    };
+    use ark_std::{test_rng, UniformRand};
+    use std::{ops::Neg, time::Instant};
+
+    fn long_division(
+        dividend: &DensePolynomial<Fr>,
+        divisor: &DensePolynomial<Fr>,
+    ) -> (DensePolynomial<Fr>, DensePolynomial<Fr>) {
+        DenseOrSparsePolynomial::divide_with_q_and_r(&dividend.into(), &divisor.into()).unwrap()
+    }
+
+    #[test]
+    fn test_divide_by_linear() {
+        let degree = 1 << 14;
+        let mut rng = test_rng();
+        let poly = DensePolynomial::<Fr>::rand(degree, &mut rng);
+        let z = Fr::rand(&mut rng);
+
+        let now = Instant::now();
+        let (quotient, remainder) = divide_by_linear(&poly, &z);
+        println!(
+            "synthetic division of degree {}: {:?}",
+            degree,
+            now.elapsed()
+        );
+
+        let now = Instant::now();
+        let divisor = DensePolynomial::from_coefficients_slice(&[z.neg(), Fr::one()]);
+        let (expect_quotient, expect_remainder) = long_division(&poly, &divisor);
+        println!("long division of degree {}: {:?}", degree, now.elapsed());
+
+        assert_eq!(quotient, expect_quotient);
+        assert_eq!(remainder, poly.evaluate(&z));
+        assert_eq!(expect_remainder.coeffs, vec![remainder]);
+
+        let (quotient, remainder) = divide_by_linear(&DensePolynomial::zero(), &z);
+        assert!(quotient.is_zero() && remainder.is_zero());
+    }
+
+    #[test]
+    fn test_divide_by_vanishing_poly() {
+        let degree = 1 << 14;
+        let mut rng = test_rng();
+        let points = (0..3).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        let mut divisor = DensePolynomial::from_coefficients_slice(&[Fr::one()]);
+        for point in points.iter() {
+            divisor = divisor.naive_mul(&DensePolynomial::from_coefficients_slice(&[
+                -*point,
+                Fr::one(),
+            ]));
+        }
+        let dividend =
+            DensePolynomial::<Fr>::rand(degree - points.len(), &mut rng).naive_mul(&divisor);
+
+        let now = Instant::now();
+        let quotient = divide_by_vanishing_poly(&dividend, &points).unwrap();
+        println!("coset division of degree {}: {:?}", degree, now.elapsed());
+
+        let now = Instant::now();
+        let (expect_quotient, remainder) = long_division(&dividend, &divisor);
+        println!("long division of degree {}: {:?}", degree, now.elapsed());
+
+        assert!(remainder.is_zero());
+        assert_eq!(quotient, expect_quotient);
+
+        // a point in the first coset.
+        let points = [Fr::from(7u64), Fr::one()];
+        let dividend = DensePolynomial::from_coefficients_slice(&[Fr::from(3u64), Fr::one()])
+            .naive_mul(&DensePolynomial::from_coefficients_slice(&[
+                -points[0],
+                Fr::one(),
+            ]))
+            .naive_mul(&DensePolynomial::from_coefficients_slice(&[
+                -points[1],
+                Fr::one(),
+            ]));
+        let quotient = divide_by_vanishing_poly(&dividend, &points).unwrap();
+        assert_eq!(quotient.coeffs, vec![Fr::from(3u64), Fr::one()]);
+    }
+}
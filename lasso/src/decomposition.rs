@@ -0,0 +1,104 @@
+use ark_ff::PrimeField;
+use plookup::tables::BitwiseOp;
+
+/// How a lookup into a big table T splits into lookups into small subtables T_0, ..., T_{c - 1}:
+/// T[index] = g(T_0[split(index)_0], ..., T_{c - 1}[split(index)_{c - 1}]).
+///
+/// T is never materialized, only the subtables are.
+pub trait Decomposition<F: PrimeField> {
+    /// The number c of chunks.
+    fn num_chunks(&self) -> usize;
+
+    /// Every subtable has 2^subtable_bits entries.
+    fn subtable_bits(&self) -> usize;
+
+    /// The subtable T_i the chunk i looks up.
+    fn subtable(&self, chunk: usize) -> Vec<F>;
+
+    /// Splits an index of T into an index of every subtable.
+    fn split(&self, index: u128) -> Vec<usize>;
+
+    /// g as a sum of products of the chunk entries, a (coefficient, chunks) pair for every term.
+    fn combine(&self) -> Vec<(F, Vec<usize>)>;
+
+    /// T[index], from the entries of its chunks.
+    fn lookup(&self, index: u128) -> F {
+        let entries = self
+            .split(index)
+            .into_iter()
+            .enumerate()
+            .map(|(chunk, i)| self.subtable(chunk)[i])
+            .collect::<Vec<_>>();
+        self.combine()
+            .iter()
+            .map(|(coeff, chunks)| chunks.iter().fold(*coeff, |acc, i| acc * entries[*i]))
+            .sum()
+    }
+}
+
+/// a op b for `bits`-bit operands, at the index (a << bits) | b, split into `chunk_bits`-bit
+/// chunks of a and b: `Bitwise::new(BitwiseOp::And, 64, 8)` is the 64-bit AND as 8 byte ANDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bitwise {
+    op: BitwiseOp,
+    bits: usize,
+    chunk_bits: usize,
+}
+
+impl Bitwise {
+    pub fn new(op: BitwiseOp, bits: usize, chunk_bits: usize) -> Self {
+        assert!(bits <= 64, "A bitwise table holds at most 64-bit operands");
+        assert!(
+            chunk_bits <= 16,
+            "A bitwise subtable holds at most 16-bit operands"
+        );
+        assert_eq!(bits % chunk_bits, 0);
+
+        Bitwise {
+            op,
+            bits,
+            chunk_bits,
+        }
+    }
+
+    pub fn index(&self, a: u64, b: u64) -> u128 {
+        ((a as u128) << self.bits) | b as u128
+    }
+}
+
+impl<F: PrimeField> Decomposition<F> for Bitwise {
+    fn num_chunks(&self) -> usize {
+        self.bits / self.chunk_bits
+    }
+
+    fn subtable_bits(&self) -> usize {
+        2 * self.chunk_bits
+    }
+
+    /// (a_i op b_i) at (a_i << chunk_bits) | b_i, the same for every chunk.
+    fn subtable(&self, _chunk: usize) -> Vec<F> {
+        let mask = (1 << self.chunk_bits) - 1;
+        (0..1u64 << (2 * self.chunk_bits))
+            .map(|i| F::from(self.op.apply(i >> self.chunk_bits, i & mask)))
+            .collect()
+    }
+
+    /// The chunk i holds the bits [i * chunk_bits, (i + 1) * chunk_bits) of a and b.
+    fn split(&self, index: u128) -> Vec<usize> {
+        let mask = (1u128 << self.chunk_bits) - 1;
+        let (a, b) = (index >> self.bits, index & ((1u128 << self.bits) - 1));
+        (0..self.bits / self.chunk_bits)
+            .map(|i| {
+                let shift = i * self.chunk_bits;
+                ((((a >> shift) & mask) << self.chunk_bits) | ((b >> shift) & mask)) as usize
+            })
+            .collect()
+    }
+
+    /// \sum_i 2^{i * chunk_bits} * E_i
+    fn combine(&self) -> Vec<(F, Vec<usize>)> {
+        (0..self.bits / self.chunk_bits)
+            .map(|i| (F::from(2u64).pow([(i * self.chunk_bits) as u64]), vec![i]))
+            .collect()
+    }
+}
@@ -0,0 +1,181 @@
+//! The grand product of a vector with a binary tree of multiplications, proven layer by
+//! layer with sumcheck as in GKR.
+
+use ark_ec::CurveGroup;
+use ark_ff::{One, PrimeField, Zero};
+use ark_poly::{
+    univariate::{DensePolynomial, SparsePolynomial},
+    Polynomial,
+};
+use kzg::transcripts::GlobalTranscript;
+use merlin::Transcript;
+use sumcheck::{
+    multilinear::{eq_evaluations, evaluate_mle, VirtualPolynomial},
+    prover::Prover,
+    verifier::OracleVerifier,
+    SumCheck,
+};
+
+/// Absorbs the coefficients of a round polynomial of sumcheck.
+fn append_round_poly<G: CurveGroup>(
+    transcript: &mut Transcript,
+    g_j: &SparsePolynomial<G::ScalarField>,
+) {
+    <Transcript as GlobalTranscript<G>>::append_scalars(
+        transcript,
+        &DensePolynomial::from(g_j.clone()).coeffs,
+    );
+}
+
+/// Proves the sum of `poly` over the hypercube, and returns the messages of every round with
+/// the point the verifier ends at. The challenges are drawn from `transcript`.
+pub(crate) fn prove_sumcheck<G: CurveGroup>(
+    poly: VirtualPolynomial<G::ScalarField>,
+    transcript: &mut Transcript,
+) -> (Vec<SparsePolynomial<G::ScalarField>>, Vec<G::ScalarField>) {
+    let mut prover = Prover::new(poly.clone());
+    let mut msgs = vec![];
+    let mut point = vec![];
+    let mut r_j = G::ScalarField::zero();
+    for j in 0..poly.num_round() {
+        let g_j = prover.start_round(j, r_j);
+        append_round_poly::<G>(transcript, &g_j);
+        r_j = <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"sumcheck r");
+        msgs.push(g_j);
+        point.push(r_j);
+    }
+    (msgs, point)
+}
+
+/// Checks the messages of `prove_sumcheck` for a sum `claim` over `num_vars` variables, and
+/// returns the point r with the value the polynomial should have there, which the caller
+/// checks. Panics if a message is rejected.
+pub(crate) fn verify_sumcheck<G: CurveGroup>(
+    num_vars: usize,
+    degree: usize,
+    claim: G::ScalarField,
+    msgs: &[SparsePolynomial<G::ScalarField>],
+    transcript: &mut Transcript,
+) -> (Vec<G::ScalarField>, G::ScalarField) {
+    assert_eq!(msgs.len(), num_vars);
+
+    let mut verifier = OracleVerifier::new(num_vars, claim);
+    for (j, g_j) in msgs.iter().enumerate() {
+        assert!(g_j.degree() <= degree);
+        append_round_poly::<G>(transcript, g_j);
+        let r_j = <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"sumcheck r");
+        verifier.receive_msg_with_challenge(j, g_j.clone(), r_j);
+    }
+    verifier.sub_claim()
+}
+
+/// eq(z, x) = \prod (z_i * x_i + (1 - z_i) * (1 - x_i))
+pub(crate) fn eq_eval<F: PrimeField>(z: &[F], x: &[F]) -> F {
+    z.iter()
+        .zip(x.iter())
+        .map(|(z_i, x_i)| *z_i * x_i + (F::one() - z_i) * (F::one() - x_i))
+        .product()
+}
+
+/// The messages of `prove_grand_product`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrandProductProof<F: PrimeField> {
+    /// The layer with 2 variables, sent in the clear.
+    pub top: Vec<F>,
+    /// From the top down, the sumcheck messages of a layer with V_{k + 1}(ρ, 0) and
+    /// V_{k + 1}(ρ, 1).
+    pub layers: Vec<(Vec<SparsePolynomial<F>>, F, F)>,
+}
+
+/// Proves \prod `leaves`, whose length is a power of two of at least 4, and returns the
+/// point z the verifier ends at, where the caller opens the leaves.
+///
+/// V_k[i] = V_{k + 1}[2i] * V_{k + 1}[2i + 1], from the leaves up to the 4 entries of the
+/// layer with 2 variables, which are sent in the clear. A claim V_k(z) is the sum of
+/// eq(z, x) * V_{k + 1}(x, 0) * V_{k + 1}(x, 1) over the hypercube, so sumcheck reduces it to
+/// V_{k + 1}(ρ, 0) and V_{k + 1}(ρ, 1), which are folded into V_{k + 1}(ρ, μ).
+pub fn prove_grand_product<G: CurveGroup>(
+    leaves: &[G::ScalarField],
+    transcript: &mut Transcript,
+) -> (GrandProductProof<G::ScalarField>, Vec<G::ScalarField>) {
+    assert!(leaves.len() >= 4 && leaves.len().is_power_of_two());
+
+    let mut layers = vec![leaves.to_vec()];
+    while layers.last().unwrap().len() > 4 {
+        let layer = layers
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| pair[0] * pair[1])
+            .collect();
+        layers.push(layer);
+    }
+
+    let top = layers.last().unwrap().clone();
+    <Transcript as GlobalTranscript<G>>::append_scalars(transcript, &top);
+    let mut z = vec![
+        <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"grand product z_0"),
+        <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"grand product z_1"),
+    ];
+
+    let mut proof_layers = vec![];
+    for layer in layers.iter().rev().skip(1) {
+        let left = layer.iter().step_by(2).copied().collect::<Vec<_>>();
+        let right = layer.iter().skip(1).step_by(2).copied().collect::<Vec<_>>();
+        let poly = VirtualPolynomial::new(
+            z.len(),
+            vec![eq_evaluations(&z), left.clone(), right.clone()],
+            vec![(G::ScalarField::one(), vec![0, 1, 2])],
+        );
+        let (msgs, rho) = prove_sumcheck::<G>(poly, transcript);
+
+        let (l, r) = (evaluate_mle(&left, &rho), evaluate_mle(&right, &rho));
+        <Transcript as GlobalTranscript<G>>::append_scalars(transcript, &[l, r]);
+        let mu =
+            <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"grand product mu");
+        z = rho;
+        z.push(mu);
+        proof_layers.push((msgs, l, r));
+    }
+
+    let proof = GrandProductProof {
+        top,
+        layers: proof_layers,
+    };
+    (proof, z)
+}
+
+/// Checks a proof of `prove_grand_product` over 2^`num_vars` leaves. Returns the product, and
+/// the point z with the value the multilinear extension of the leaves should have there, which
+/// the caller checks against the leaves.
+pub fn verify_grand_product<G: CurveGroup>(
+    proof: &GrandProductProof<G::ScalarField>,
+    num_vars: usize,
+    transcript: &mut Transcript,
+) -> (G::ScalarField, Vec<G::ScalarField>, G::ScalarField) {
+    assert!(num_vars >= 2);
+    assert_eq!(proof.top.len(), 4);
+    assert_eq!(proof.layers.len(), num_vars - 2);
+
+    let product = proof.top.iter().product();
+    <Transcript as GlobalTranscript<G>>::append_scalars(transcript, &proof.top);
+    let mut z = vec![
+        <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"grand product z_0"),
+        <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"grand product z_1"),
+    ];
+    let mut claim = evaluate_mle(&proof.top, &z);
+
+    for (msgs, l, r) in proof.layers.iter() {
+        let (rho, eval) = verify_sumcheck::<G>(z.len(), 3, claim, msgs, transcript);
+        assert_eq!(eq_eval(&z, &rho) * l * r, eval);
+
+        <Transcript as GlobalTranscript<G>>::append_scalars(transcript, &[*l, *r]);
+        let mu =
+            <Transcript as GlobalTranscript<G>>::get_challenge(transcript, b"grand product mu");
+        claim = *l + mu * (*r - l);
+        z = rho;
+        z.push(mu);
+    }
+
+    (product, z, claim)
+}
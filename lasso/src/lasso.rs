@@ -0,0 +1,492 @@
+//! Lookups into tables too large to materialize, in the spirit of Lasso
+//! (https://eprint.iacr.org/2023/1216.pdf).
+//!
+//! Every lookup splits into chunk lookups E_i = T_i[dim_i] into small subtables, as described
+//! by a `Decomposition`. Sumcheck proves the outputs are g(E_0, ..., E_{c - 1}), and offline
+//! memory checking proves each E_i was read from T_i: the multisets
+//! init ∪ writes and reads ∪ final of (address, value, counter) tuples are equal, which is
+//! checked on the grand products of their fingerprints.
+//!
+//! The outputs, the entries and the counters are committed with `kzg::multilinear`, and the
+//! final queries of every sumcheck are answered with openings of these commitments. The
+//! indexes are public, the verifier evaluates the addresses and the subtables itself.
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{One, PrimeField, Zero};
+use ark_poly::univariate::SparsePolynomial;
+use kzg::{
+    multilinear::{
+        MultilinearKZGCommitmentScheme, MultilinearKZGProof, MultilinearSRS, MultilinearVerifierKey,
+    },
+    transcripts::GlobalTranscript,
+};
+use merlin::Transcript;
+use sumcheck::multilinear::{eq_evaluations, evaluate_mle, VirtualPolynomial};
+
+use crate::{
+    decomposition::Decomposition,
+    grand_product::{
+        eq_eval, prove_grand_product, prove_sumcheck, verify_grand_product, verify_sumcheck,
+        GrandProductProof,
+    },
+};
+
+/// The commitments and the messages sent by the prover, every vector is indexed by chunk first.
+#[derive(Debug, Clone)]
+pub struct LassoProof<G: CurveGroup> {
+    /// [outputs], T[index] for every lookup padded with T[0] to a power of two of at least 4.
+    pub output_comm: G,
+    /// [E_i], E_i = T_i[dim_i]
+    pub entry_comms: Vec<G>,
+    /// [read_i], how many times the entry had been read before every lookup.
+    pub read_comms: Vec<G>,
+    /// [final_i], how many times every entry of the subtable has been read.
+    pub final_comms: Vec<G>,
+    /// The rounds of the sumcheck of the outputs.
+    pub sumcheck: Vec<SparsePolynomial<G::ScalarField>>,
+    /// outputs(r)
+    pub output_opening: MultilinearKZGProof<G>,
+    /// E_i at the point the sumcheck of the outputs ends at.
+    pub entry_openings: Vec<MultilinearKZGProof<G>>,
+    /// The grand products of init, final, reads and writes of every chunk.
+    pub grand_products: Vec<[GrandProductProof<G::ScalarField>; 4]>,
+    /// final_i at the point the grand product of final ends at, then E_i and read_i at the
+    /// points of reads and of writes.
+    pub memory_openings: Vec<[MultilinearKZGProof<G>; 5]>,
+}
+
+pub struct Lasso<D> {
+    decomposition: D,
+}
+
+/// Pads the lookups with index 0 to a power of two of at least 4.
+fn pad_indexes(indexes: &[u128]) -> Vec<u128> {
+    let mut indexes = indexes.to_vec();
+    indexes.resize(indexes.len().next_power_of_two().max(4), 0);
+    indexes
+}
+
+/// Absorbs the lookups, and the commitments to the outputs and to the entries and counters of
+/// every chunk.
+fn append_lookups<G: CurveGroup>(
+    transcript: &mut Transcript,
+    indexes: &[u128],
+    output_comm: &G,
+    entry_comms: &[G],
+    read_comms: &[G],
+    final_comms: &[G],
+) {
+    let indexes = indexes
+        .iter()
+        .map(|i| G::ScalarField::from(*i))
+        .collect::<Vec<_>>();
+    <Transcript as GlobalTranscript<G>>::append_scalars(transcript, &indexes);
+    transcript.append_commitment(output_comm);
+    for chunk in 0..entry_comms.len() {
+        transcript.append_commitment(&entry_comms[chunk]);
+        transcript.append_commitment(&read_comms[chunk]);
+        transcript.append_commitment(&final_comms[chunk]);
+    }
+}
+
+/// g(E_0, ..., E_{c - 1}) from the terms of `Decomposition::combine`.
+fn combine_entries<F: PrimeField>(terms: &[(F, Vec<usize>)], entries: &[F]) -> F {
+    terms
+        .iter()
+        .map(|(coeff, chunks)| chunks.iter().fold(*coeff, |acc, i| acc * entries[*i]))
+        .sum()
+}
+
+fn to_scalars<F: PrimeField>(values: &[u64]) -> Vec<F> {
+    values.iter().map(|v| F::from(*v)).collect()
+}
+
+impl<D> Lasso<D> {
+    pub fn new(decomposition: D) -> Self {
+        Lasso { decomposition }
+    }
+
+    /// dim_i for every lookup, indexed by chunk first.
+    fn dims<F: PrimeField>(&self, indexes: &[u128]) -> Vec<Vec<usize>>
+    where
+        D: Decomposition<F>,
+    {
+        let splits = indexes
+            .iter()
+            .map(|index| self.decomposition.split(*index))
+            .collect::<Vec<_>>();
+        (0..self.decomposition.num_chunks())
+            .map(|chunk| splits.iter().map(|split| split[chunk]).collect())
+            .collect()
+    }
+
+    /// How many times the entry had been read before every lookup, and how many times every
+    /// entry of the subtable has been read, indexed by chunk first.
+    pub(crate) fn counts<F: PrimeField>(&self, indexes: &[u128]) -> (Vec<Vec<u64>>, Vec<Vec<u64>>)
+    where
+        D: Decomposition<F>,
+    {
+        let subtable_size = 1 << self.decomposition.subtable_bits();
+        let mut read_counts = vec![];
+        let mut final_counts = vec![];
+        for dim in self.dims(indexes) {
+            let mut counts = vec![0u64; subtable_size];
+            let reads = dim
+                .iter()
+                .map(|i| {
+                    counts[*i] += 1;
+                    counts[*i] - 1
+                })
+                .collect();
+            read_counts.push(reads);
+            final_counts.push(counts);
+        }
+        (read_counts, final_counts)
+    }
+
+    /// E_i = T_i[dim_i] for every lookup, indexed by chunk first.
+    pub(crate) fn entries<F: PrimeField>(&self, indexes: &[u128]) -> Vec<Vec<F>>
+    where
+        D: Decomposition<F>,
+    {
+        self.dims(indexes)
+            .into_iter()
+            .enumerate()
+            .map(|(chunk, dim)| {
+                let subtable = self.decomposition.subtable(chunk);
+                dim.iter().map(|i| subtable[*i]).collect()
+            })
+            .collect()
+    }
+
+    /// eq(r, j) * g(E_0(j), ..., E_{c - 1}(j)), whose sum over j is outputs(r).
+    fn output_poly<F: PrimeField>(&self, r: &[F], entries: &[Vec<F>]) -> VirtualPolynomial<F>
+    where
+        D: Decomposition<F>,
+    {
+        let mut mles = vec![eq_evaluations(r)];
+        mles.extend(entries.iter().cloned());
+        let terms = self
+            .decomposition
+            .combine()
+            .into_iter()
+            .map(|(coeff, chunks)| {
+                let mut factors = vec![0];
+                factors.extend(chunks.into_iter().map(|i| i + 1));
+                (coeff, factors)
+            })
+            .collect();
+        VirtualPolynomial::new(r.len(), mles, terms)
+    }
+
+    /// The fingerprints of init, final, reads and writes of `chunk`.
+    #[allow(clippy::too_many_arguments)]
+    fn memory_fingerprints<F: PrimeField>(
+        &self,
+        chunk: usize,
+        dims: &[usize],
+        entries: &[F],
+        read_counts: &[F],
+        final_counts: &[F],
+        gamma: F,
+        tau: F,
+    ) -> [Vec<F>; 4]
+    where
+        D: Decomposition<F>,
+    {
+        let fingerprint = |address: usize, value: F, count: F| {
+            F::from(address as u64) * gamma * gamma + value * gamma + count - tau
+        };
+        let subtable = self.decomposition.subtable(chunk);
+        let num_lookups = dims.len();
+
+        let init = (0..subtable.len())
+            .map(|a| fingerprint(a, subtable[a], F::zero()))
+            .collect::<Vec<_>>();
+        let finals = (0..subtable.len())
+            .map(|a| fingerprint(a, subtable[a], final_counts[a]))
+            .collect::<Vec<_>>();
+        let reads = (0..num_lookups)
+            .map(|j| fingerprint(dims[j], entries[j], read_counts[j]))
+            .collect::<Vec<_>>();
+        let writes = (0..num_lookups)
+            .map(|j| fingerprint(dims[j], entries[j], read_counts[j] + F::one()))
+            .collect::<Vec<_>>();
+
+        [init, finals, reads, writes]
+    }
+
+    /// Returns T[index] for the lookups `indexes`, padded with T[0] to a power of two of at
+    /// least 4, with the proof they are. `pk` should hold polynomials in as many variables as
+    /// the padded lookups and as a subtable.
+    pub fn prove<P: Pairing>(
+        &self,
+        pk: &MultilinearSRS<P>,
+        indexes: &[u128],
+    ) -> (Vec<P::ScalarField>, LassoProof<P::G1>)
+    where
+        D: Decomposition<P::ScalarField>,
+    {
+        let indexes = pad_indexes(indexes);
+        let entries = self.entries(&indexes);
+        let terms = self.decomposition.combine();
+        let outputs = (0..indexes.len())
+            .map(|j| {
+                let entries = entries.iter().map(|e| e[j]).collect::<Vec<_>>();
+                combine_entries(&terms, &entries)
+            })
+            .collect::<Vec<_>>();
+
+        let proof = self.prove_with_entries(pk, &indexes, outputs.clone(), entries);
+        (outputs, proof)
+    }
+
+    /// Runs the protocol from the claimed `outputs` and chunk `entries` of the padded
+    /// `indexes`, which are not checked here.
+    pub(crate) fn prove_with_entries<P: Pairing>(
+        &self,
+        pk: &MultilinearSRS<P>,
+        indexes: &[u128],
+        outputs: Vec<P::ScalarField>,
+        entries: Vec<Vec<P::ScalarField>>,
+    ) -> LassoProof<P::G1>
+    where
+        D: Decomposition<P::ScalarField>,
+    {
+        let (read_counts, final_counts) = self.counts(indexes);
+        self.prove_with_counts(pk, indexes, outputs, entries, read_counts, final_counts)
+    }
+
+    /// Runs the protocol from the claimed `outputs`, chunk `entries` and counters of the padded
+    /// `indexes`, which are not checked here.
+    pub(crate) fn prove_with_counts<P: Pairing>(
+        &self,
+        pk: &MultilinearSRS<P>,
+        indexes: &[u128],
+        outputs: Vec<P::ScalarField>,
+        entries: Vec<Vec<P::ScalarField>>,
+        read_counts: Vec<Vec<u64>>,
+        final_counts: Vec<Vec<u64>>,
+    ) -> LassoProof<P::G1>
+    where
+        D: Decomposition<P::ScalarField>,
+    {
+        let scheme = MultilinearKZGCommitmentScheme(pk);
+        let commit = |evals: &[P::ScalarField]| scheme.commit(evals).unwrap();
+        let open = |evals: &[P::ScalarField], point: &[P::ScalarField]| {
+            scheme.prove(evals, point).unwrap()
+        };
+
+        let dims = self.dims(indexes);
+        let read_evals = read_counts
+            .iter()
+            .map(|c| to_scalars(c))
+            .collect::<Vec<_>>();
+        let final_evals = final_counts
+            .iter()
+            .map(|c| to_scalars(c))
+            .collect::<Vec<_>>();
+
+        let output_comm = commit(&outputs);
+        let entry_comms = entries.iter().map(|e| commit(e)).collect::<Vec<_>>();
+        let read_comms = read_evals.iter().map(|c| commit(c)).collect::<Vec<_>>();
+        let final_comms = final_evals.iter().map(|c| commit(c)).collect::<Vec<_>>();
+        let mut transcript = Transcript::new(b"lasso");
+        append_lookups(
+            &mut transcript,
+            indexes,
+            &output_comm,
+            &entry_comms,
+            &read_comms,
+            &final_comms,
+        );
+
+        // 1. \sum_j eq(r, j) * g(E_0(j), ..., E_{c - 1}(j)) = outputs(r)
+        let r = (0..indexes.len().trailing_zeros())
+            .map(|_| <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"r"))
+            .collect::<Vec<_>>();
+        let (sumcheck, point) =
+            prove_sumcheck::<P::G1>(self.output_poly(&r, &entries), &mut transcript);
+        let output_opening = open(&outputs, &r);
+        let entry_openings = entries.iter().map(|e| open(e, &point)).collect();
+
+        // 2. E_i has been read from T_i at dim_i.
+        let gamma =
+            <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"gamma");
+        let tau = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"tau");
+        let mut grand_products = vec![];
+        let mut memory_openings = vec![];
+        for (chunk, dim) in dims.iter().enumerate() {
+            let fingerprints = self.memory_fingerprints(
+                chunk,
+                dim,
+                &entries[chunk],
+                &read_evals[chunk],
+                &final_evals[chunk],
+                gamma,
+                tau,
+            );
+            let [init, finals, reads, writes] =
+                fingerprints.map(|leaves| prove_grand_product::<P::G1>(&leaves, &mut transcript));
+
+            memory_openings.push([
+                open(&final_evals[chunk], &finals.1),
+                open(&entries[chunk], &reads.1),
+                open(&read_evals[chunk], &reads.1),
+                open(&entries[chunk], &writes.1),
+                open(&read_evals[chunk], &writes.1),
+            ]);
+            grand_products.push([init.0, finals.0, reads.0, writes.0]);
+        }
+
+        LassoProof {
+            output_comm,
+            entry_comms,
+            read_comms,
+            final_comms,
+            sumcheck,
+            output_opening,
+            entry_openings,
+            grand_products,
+            memory_openings,
+        }
+    }
+
+    /// Panics if `proof.output_comm` is not a commitment to T[index] for the lookups
+    /// `indexes`, padded as in `prove`.
+    pub fn verify<P: Pairing>(
+        &self,
+        vk: &MultilinearVerifierKey<P>,
+        indexes: &[u128],
+        proof: &LassoProof<P::G1>,
+    ) where
+        D: Decomposition<P::ScalarField>,
+    {
+        let indexes = pad_indexes(indexes);
+        let num_lookups = indexes.len();
+        let num_chunks = self.decomposition.num_chunks();
+        let subtable_bits = self.decomposition.subtable_bits();
+        assert!(subtable_bits >= 2);
+        assert_eq!(proof.entry_comms.len(), num_chunks);
+        assert_eq!(proof.read_comms.len(), num_chunks);
+        assert_eq!(proof.final_comms.len(), num_chunks);
+        assert_eq!(proof.entry_openings.len(), num_chunks);
+        assert_eq!(proof.grand_products.len(), num_chunks);
+        assert_eq!(proof.memory_openings.len(), num_chunks);
+
+        let open =
+            |comm: &P::G1, point: &[P::ScalarField], opening: &MultilinearKZGProof<P::G1>| {
+                assert!(
+                    vk.verify(comm, point, opening).is_ok(),
+                    "An opening has been rejected"
+                );
+                opening.opening_value()
+            };
+
+        let mut transcript = Transcript::new(b"lasso");
+        append_lookups(
+            &mut transcript,
+            &indexes,
+            &proof.output_comm,
+            &proof.entry_comms,
+            &proof.read_comms,
+            &proof.final_comms,
+        );
+
+        // 1. \sum_j eq(r, j) * g(E_0(j), ..., E_{c - 1}(j)) = outputs(r)
+        let r = (0..num_lookups.trailing_zeros())
+            .map(|_| <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"r"))
+            .collect::<Vec<_>>();
+        let terms = self.decomposition.combine();
+        let degree = 1 + terms
+            .iter()
+            .map(|(_, chunks)| chunks.len())
+            .max()
+            .unwrap_or(0);
+        let (point, eval) = verify_sumcheck::<P::G1>(
+            r.len(),
+            degree,
+            open(&proof.output_comm, &r, &proof.output_opening),
+            &proof.sumcheck,
+            &mut transcript,
+        );
+        let entries = proof
+            .entry_comms
+            .iter()
+            .zip(proof.entry_openings.iter())
+            .map(|(comm, opening)| open(comm, &point, opening))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            eq_eval(&r, &point) * combine_entries(&terms, &entries),
+            eval
+        );
+
+        // 2. E_i has been read from T_i at dim_i.
+        let gamma =
+            <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"gamma");
+        let tau = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"tau");
+        // the fingerprints are affine in (address, value, count), so their multilinear
+        // extension at z is the fingerprint of the multilinear extensions at z.
+        let fingerprint =
+            |address: P::ScalarField, value: P::ScalarField, count: P::ScalarField| {
+                address * gamma * gamma + value * gamma + count - tau
+            };
+        let addresses = (0..1u64 << subtable_bits)
+            .map(P::ScalarField::from)
+            .collect::<Vec<_>>();
+        for (chunk, dim) in self.dims(&indexes).iter().enumerate() {
+            let subtable = self.decomposition.subtable(chunk);
+            let dim = dim
+                .iter()
+                .map(|i| P::ScalarField::from(*i as u64))
+                .collect::<Vec<_>>();
+            let (entry_comm, read_comm) = (&proof.entry_comms[chunk], &proof.read_comms[chunk]);
+            let [init, finals, reads, writes] = &proof.grand_products[chunk];
+            let [final_opening, read_entry, read_count, write_entry, write_count] =
+                &proof.memory_openings[chunk];
+
+            let (init, z, claim) =
+                verify_grand_product::<P::G1>(init, subtable_bits, &mut transcript);
+            let expected = fingerprint(
+                evaluate_mle(&addresses, &z),
+                evaluate_mle(&subtable, &z),
+                P::ScalarField::zero(),
+            );
+            assert_eq!(expected, claim);
+
+            let (finals, z, claim) =
+                verify_grand_product::<P::G1>(finals, subtable_bits, &mut transcript);
+            let expected = fingerprint(
+                evaluate_mle(&addresses, &z),
+                evaluate_mle(&subtable, &z),
+                open(&proof.final_comms[chunk], &z, final_opening),
+            );
+            assert_eq!(expected, claim);
+
+            let num_vars = num_lookups.trailing_zeros() as usize;
+            let (reads, z, claim) = verify_grand_product::<P::G1>(reads, num_vars, &mut transcript);
+            let expected = fingerprint(
+                evaluate_mle(&dim, &z),
+                open(entry_comm, &z, read_entry),
+                open(read_comm, &z, read_count),
+            );
+            assert_eq!(expected, claim);
+
+            let (writes, z, claim) =
+                verify_grand_product::<P::G1>(writes, num_vars, &mut transcript);
+            let expected = fingerprint(
+                evaluate_mle(&dim, &z),
+                open(entry_comm, &z, write_entry),
+                open(read_comm, &z, write_count) + P::ScalarField::one(),
+            );
+            assert_eq!(expected, claim);
+
+            assert_eq!(
+                init * writes,
+                reads * finals,
+                "The chunk {} has not been read from its subtable",
+                chunk
+            );
+        }
+    }
+}
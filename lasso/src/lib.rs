@@ -0,0 +1,5 @@
+pub mod decomposition;
+pub mod grand_product;
+pub mod lasso;
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,177 @@
+use crate::{
+    decomposition::{Bitwise, Decomposition},
+    grand_product::{prove_grand_product, verify_grand_product},
+    lasso::Lasso,
+};
+use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+use ark_ff::{Field, UniformRand};
+use ark_std::{rand::RngCore, test_rng};
+use kzg::multilinear::{MultilinearKZGCommitmentScheme, MultilinearSRS};
+use merlin::Transcript;
+use plookup::tables::BitwiseOp;
+use sumcheck::multilinear::evaluate_mle;
+
+#[test]
+fn test_grand_product() {
+    let mut rng = test_rng();
+    let leaves = (0..32).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+    let (proof, point) =
+        prove_grand_product::<G1Projective>(&leaves, &mut Transcript::new(b"test"));
+    let (product, z, claim) =
+        verify_grand_product::<G1Projective>(&proof, 5, &mut Transcript::new(b"test"));
+    assert_eq!(product, leaves.iter().product::<Fr>());
+    assert_eq!(z, point);
+    assert_eq!(evaluate_mle(&leaves, &z), claim);
+}
+
+#[test]
+#[should_panic]
+fn test_grand_product_rejects_tampered_layer() {
+    let mut rng = test_rng();
+    let leaves = (0..32).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+
+    let (mut proof, _) =
+        prove_grand_product::<G1Projective>(&leaves, &mut Transcript::new(b"test"));
+    proof.layers[1].1 += Fr::ONE;
+    verify_grand_product::<G1Projective>(&proof, 5, &mut Transcript::new(b"test"));
+}
+
+#[test]
+fn test_lasso_64_bit_and() {
+    let mut rng = test_rng();
+    let and = Bitwise::new(BitwiseOp::And, 64, 4);
+    let operands = (0..10)
+        .map(|_| (rng.next_u64(), rng.next_u64()))
+        .collect::<Vec<_>>();
+    let indexes = operands
+        .iter()
+        .map(|(a, b)| and.index(*a, *b))
+        .collect::<Vec<_>>();
+
+    let srs = MultilinearSRS::<Bls12_381>::new(8, &mut rng);
+    let (pk, vk) = srs.trim(8).unwrap();
+    let lasso = Lasso::new(and);
+    let (outputs, proof) = lasso.prove(&pk, &indexes);
+    for ((a, b), output) in operands.iter().zip(outputs.iter()) {
+        assert_eq!(*output, Fr::from(a & b));
+    }
+    assert_eq!(outputs[10..], [Decomposition::<Fr>::lookup(&and, 0); 6]);
+    let scheme = MultilinearKZGCommitmentScheme(&pk);
+    assert_eq!(proof.output_comm, scheme.commit(&outputs).unwrap());
+
+    lasso.verify(&vk, &indexes, &proof);
+}
+
+#[test]
+fn test_lasso_16_bit_xor_in_bytes() {
+    let xor = Bitwise::new(BitwiseOp::Xor, 16, 8);
+    let indexes =
+        [(0xabcd, 0x1234), (0xffff, 0x00ff), (7, 7), (0, 0xbeef)].map(|(a, b)| xor.index(a, b));
+
+    let (pk, vk) = MultilinearSRS::<Bls12_381>::new(16, &mut test_rng())
+        .trim(16)
+        .unwrap();
+    let lasso = Lasso::new(xor);
+    let (outputs, proof) = lasso.prove(&pk, &indexes);
+    assert_eq!(outputs, [0xabcd ^ 0x1234, 0xff00, 0, 0xbeef].map(Fr::from));
+
+    lasso.verify(&vk, &indexes, &proof);
+}
+
+#[test]
+#[should_panic(expected = "has not been read from its subtable")]
+fn test_lasso_rejects_entry_outside_subtable() {
+    let and = Bitwise::new(BitwiseOp::And, 16, 4);
+    let indexes = [and.index(0x0f0f, 0x00ff), and.index(3, 5)];
+
+    let (pk, vk) = MultilinearSRS::<Bls12_381>::new(8, &mut test_rng())
+        .trim(8)
+        .unwrap();
+    let lasso = Lasso::new(and);
+    let padded = [indexes[0], indexes[1], 0, 0];
+    let (mut outputs, _) = lasso.prove(&pk, &indexes);
+    let mut entries = lasso.entries(&padded);
+    // 0x0f0f & 0x00ff claimed to be 0xff, the outputs still agree with the chunk entries.
+    entries[1][0] = Fr::from(0xf);
+    outputs[0] = Fr::from(0xff);
+    let proof = lasso.prove_with_entries(&pk, &padded, outputs, entries);
+
+    lasso.verify(&vk, &indexes, &proof);
+}
+
+#[test]
+#[should_panic]
+fn test_lasso_rejects_wrong_output() {
+    let and = Bitwise::new(BitwiseOp::And, 16, 4);
+    let indexes = [and.index(0x0f0f, 0x00ff), and.index(3, 5)];
+
+    let (pk, vk) = MultilinearSRS::<Bls12_381>::new(8, &mut test_rng())
+        .trim(8)
+        .unwrap();
+    let lasso = Lasso::new(and);
+    let padded = [indexes[0], indexes[1], 0, 0];
+    let (mut outputs, _) = lasso.prove(&pk, &indexes);
+    outputs[1] += Fr::ONE;
+    let proof = lasso.prove_with_entries(&pk, &padded, outputs, lasso.entries(&padded));
+
+    lasso.verify(&vk, &indexes, &proof);
+}
+
+#[test]
+#[should_panic(expected = "The chunk 0 has not been read from its subtable")]
+fn test_lasso_rejects_overflowing_counter() {
+    let and = Bitwise::new(BitwiseOp::And, 16, 4);
+    let indexes = [and.index(0x0f0f, 0x00ff), and.index(3, 5)];
+
+    let (pk, vk) = MultilinearSRS::<Bls12_381>::new(8, &mut test_rng())
+        .trim(8)
+        .unwrap();
+    let lasso = Lasso::new(and);
+    let padded = [indexes[0], indexes[1], 0, 0];
+    let (outputs, _) = lasso.prove(&pk, &indexes);
+    // a write of u64::MAX + 1 is a field element, not a wrapped or panicking u64.
+    let (mut read_counts, final_counts) = lasso.counts::<Fr>(&padded);
+    read_counts[0][0] = u64::MAX;
+    let proof = lasso.prove_with_counts(
+        &pk,
+        &padded,
+        outputs,
+        lasso.entries(&padded),
+        read_counts,
+        final_counts,
+    );
+
+    lasso.verify(&vk, &indexes, &proof);
+}
+
+#[test]
+#[should_panic(expected = "An opening has been rejected")]
+fn test_lasso_rejects_swapped_commitments() {
+    let and = Bitwise::new(BitwiseOp::And, 16, 4);
+    let indexes = [and.index(0x0f0f, 0x00ff), and.index(3, 5)];
+
+    let (pk, vk) = MultilinearSRS::<Bls12_381>::new(8, &mut test_rng())
+        .trim(8)
+        .unwrap();
+    let lasso = Lasso::new(and);
+    let (_, mut proof) = lasso.prove(&pk, &indexes);
+    proof.entry_comms.swap(0, 1);
+
+    lasso.verify(&vk, &indexes, &proof);
+}
+
+#[test]
+#[should_panic]
+fn test_lasso_rejects_other_indexes() {
+    let and = Bitwise::new(BitwiseOp::And, 16, 4);
+    let indexes = [and.index(0x0f0f, 0x00ff), and.index(3, 5)];
+
+    let (pk, vk) = MultilinearSRS::<Bls12_381>::new(8, &mut test_rng())
+        .trim(8)
+        .unwrap();
+    let lasso = Lasso::new(and);
+    let (_, proof) = lasso.prove(&pk, &indexes);
+    // the outputs of 0x0f0f & 0x00ff and 3 & 5, claimed for 0xffff & 0xffff.
+    lasso.verify(&vk, &[and.index(0xffff, 0xffff), indexes[1]], &proof);
+}
@@ -0,0 +1,401 @@
+//! cq, lookups from cached quotients.
+//!
+//! The table owner commits once to Q_i(X) = L_i(X) * (T(X) - t_i) / Z_V(X) for every i of the
+//! table domain V. A proof shows \sum_K 1 / (β + f_i) = \sum_V m_j / (β + t_j), where
+//! A_j = m_j / (β + t_j) is zero outside of the lookups, so the commitments to A(X) and to its
+//! quotient by Z_V(X) combine at most n cached commitments. A proof costs O(n log n) field
+//! operations and O(n) group operations for n lookups, whatever the size N of the table.
+//!
+//! The SRS may have powers of τ beyond N, so the degrees of A(X) and B_0(X) are bounded by
+//! pairings against powers of τ in G2 up to the maximum degree D of the SRS.
+//!
+//! https://eprint.iacr.org/2022/1763.pdf
+
+use std::{
+    collections::HashMap,
+    ops::{Add, Mul, Sub},
+};
+
+use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field, One, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
+};
+use kzg::{
+    commitment::{KZGCommitmentScheme, KZGMultiOpeningProof},
+    srs::{VerifierKey, SRS},
+    transcripts::GlobalTranscript,
+};
+use merlin::Transcript;
+
+use crate::table::SampleTable;
+
+#[derive(Debug, Clone)]
+pub struct CqVerifierKey<P: Pairing> {
+    pub vk: VerifierKey<P>,
+    /// [T(τ)]_2
+    pub t_comm: P::G2,
+    /// [Z_V(τ)]_2
+    pub z_v_comm: P::G2,
+    /// [τ^{D - N + 1}]_2 bounds the degree of A(X) by N - 1.
+    pub a_shift: P::G2,
+    /// g2_shifts[k - 1] = [τ^{D - 2^k + 2}]_2 bounds the degree of B_0(X) for 2^k lookups.
+    pub g2_shifts: Vec<P::G2>,
+    pub domain: Radix2EvaluationDomain<P::ScalarField>,
+}
+
+/// A table preprocessed once by its owner, the cached commitments are indexed by V.
+pub struct CqTable<P: Pairing> {
+    table: SampleTable<P::ScalarField>,
+    indexes: HashMap<P::ScalarField, usize>,
+    /// [L_i(τ)]_1
+    lagrange_comms: Vec<P::G1Affine>,
+    /// [(L_i(τ) - L_i(0)) / τ]_1
+    lagrange_zero_comms: Vec<P::G1Affine>,
+    /// [Q_i(τ)]_1
+    quotient_comms: Vec<P::G1Affine>,
+    /// [L_i(τ) * τ^{D - N + 1}]_1
+    lagrange_shifted_comms: Vec<P::G1Affine>,
+    /// The maximum degree D of the SRS.
+    max_degree: usize,
+    vk: CqVerifierKey<P>,
+}
+
+pub struct CqProof<G: CurveGroup> {
+    pub f_comm: G,
+    pub m_comm: G,
+    pub a_comm: G,
+    pub q_a_comm: G,
+    pub a_zero_comm: G,
+    pub b_zero_comm: G,
+    pub q_b_comm: G,
+    pub p_comm: G,
+    /// [A(τ) * τ^{D - N + 1}]
+    pub p_a_comm: G,
+    /// A(0)
+    pub a_eval_zero: G::ScalarField,
+    /// B_0(γ)
+    pub b_zero_eval_gamma: G::ScalarField,
+    pub f_eval_gamma: G::ScalarField,
+    pub opening_witness: KZGMultiOpeningProof<G>,
+    /// The size n of the domain K of the lookups, the verifier rebuilds K from it.
+    pub size: usize,
+}
+
+/// \sum_k scalars[k] * bases[indexes[k]]
+fn sparse_commit<G: CurveGroup>(
+    bases: &[G::Affine],
+    indexes: &[usize],
+    scalars: &[G::ScalarField],
+) -> G {
+    let bases = indexes.iter().map(|j| bases[*j]).collect::<Vec<_>>();
+    G::msm_unchecked(&bases, scalars)
+}
+
+impl<P: Pairing> CqTable<P> {
+    /// Preprocesses `table` in O(N log N) group operations, the SRS should have the powers of τ
+    /// up to the size N of the padded table in G1, and up to both N and its maximum degree D
+    /// in G2.
+    pub fn new(mut table: SampleTable<P::ScalarField>, srs: &SRS<P>) -> Self {
+        assert!(table.size() > 0, "Look up table should not be empty");
+        table.pad();
+        let domain = Radix2EvaluationDomain::<P::ScalarField>::new(table.size()).unwrap();
+        let size = domain.size();
+        assert!(
+            srs.g1.len() >= size && srs.g2.len() > size.max(srs.g1.len() - 1),
+            "SRS should have the powers of τ up to the size of the table"
+        );
+        let max_degree = srs.g1.len() - 1;
+
+        let t_poly = DensePolynomial::from_coefficients_vec(domain.ifft(&table.0));
+        let g2_bases = P::G2::normalize_batch(&srs.g2[..t_poly.coeffs().len()]);
+        let t_comm = P::G2::msm_unchecked(&g2_bases, t_poly.coeffs());
+        let z_v_comm = srs.g2[size].sub(srs.g2[0]);
+        let a_shift = srs.g2[max_degree - size + 1];
+        let g2_shifts = (1..=domain.log_size_of_group)
+            .map(|k| srs.g2[max_degree - (1 << k) + 2])
+            .collect();
+
+        let lagrange_comms = srs.lagrange_basis(&domain).unwrap().g1;
+        let lagrange_shifted_comms = domain.ifft(&srs.g1[max_degree - size + 1..]);
+
+        // (L_i(X) - 1 / N) / X = 1 / N * \sum_{j >= 1} ω^{-ij} * X^{j - 1},
+        // the inverse FFT of (0, [τ^0], ..., [τ^{N - 2}]).
+        let mut shifted_powers = vec![P::G1::zero()];
+        shifted_powers.extend_from_slice(&srs.g1[..size - 1]);
+        let lagrange_zero_comms = domain.ifft(&shifted_powers);
+
+        // Q_i(X) = ω^i / N * (T(X) - t_i) / (X - ω^i), from the openings of T(X) over V.
+        let quotient_comms = KZGCommitmentScheme(srs)
+            .open_all_on_domain(&t_poly, &domain)
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(i, proof)| proof.comm_h().mul(domain.element(i).mul(domain.size_inv)))
+            .collect::<Vec<_>>();
+
+        let mut indexes = HashMap::new();
+        for (j, t) in table.0.iter().enumerate() {
+            indexes.entry(*t).or_insert(j);
+        }
+
+        let vk = CqVerifierKey {
+            vk: srs.trim(size).unwrap().1,
+            t_comm,
+            z_v_comm,
+            a_shift,
+            g2_shifts,
+            domain,
+        };
+
+        CqTable {
+            table,
+            indexes,
+            lagrange_comms: P::G1::normalize_batch(&lagrange_comms),
+            lagrange_zero_comms: P::G1::normalize_batch(&lagrange_zero_comms),
+            quotient_comms: P::G1::normalize_batch(&quotient_comms),
+            lagrange_shifted_comms: P::G1::normalize_batch(&lagrange_shifted_comms),
+            max_degree,
+            vk,
+        }
+    }
+
+    pub fn verifier_key(&self) -> &CqVerifierKey<P> {
+        &self.vk
+    }
+
+    /// Proves that every entry of `f_table` is in the table, `kzg_comm_scheme` should use the
+    /// SRS the table was preprocessed with.
+    pub fn prove(
+        &self,
+        f_table: &SampleTable<P::ScalarField>,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> CqProof<P::G1> {
+        let table_size = self.vk.domain.size();
+        let domain = Radix2EvaluationDomain::<P::ScalarField>::new(f_table.size().max(2)).unwrap();
+        let size = domain.size();
+        assert!(
+            size <= table_size,
+            "Prover's table should not be larger than the look up table"
+        );
+
+        let mut transcript = Transcript::new(b"cq");
+        transcript.append_commitment(&self.vk.t_comm);
+        transcript.append_u64(b"size", size as u64);
+
+        // 1.Pad f with the first entry of t, commit to f(X) and to the multiplicities m(X) over V.
+        let mut f_table = f_table.clone();
+        f_table.0.resize(size, self.table.0[0]);
+
+        let mut multiplicities = HashMap::<usize, u64>::new();
+        for f in f_table.0.iter() {
+            match self.indexes.get(f) {
+                Some(j) => *multiplicities.entry(*j).or_insert(0) += 1,
+                None => panic!("Current prover's table is not a subset of look up table"),
+            }
+        }
+        let (indexes, m_values): (Vec<usize>, Vec<P::ScalarField>) = multiplicities
+            .into_iter()
+            .map(|(j, m)| (j, P::ScalarField::from(m)))
+            .unzip();
+
+        let f_poly = DensePolynomial::from_coefficients_vec(domain.ifft(&f_table.0));
+        let f_comm = kzg_comm_scheme.commit(&f_poly).unwrap();
+        let m_comm = sparse_commit::<P::G1>(&self.lagrange_comms, &indexes, &m_values);
+        transcript.append_commitment(&f_comm);
+        transcript.append_commitment(&m_comm);
+
+        // 2.Get challenge beta, commit to A_j = m_j / (β + t_j) over V, and to
+        // B_i = 1 / (β + f_i) over K.
+        let beta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"beta");
+        let mut a_values = indexes
+            .iter()
+            .map(|j| beta.add(self.table.0[*j]))
+            .collect::<Vec<_>>();
+        batch_inversion(&mut a_values);
+        for (a, m) in a_values.iter_mut().zip(m_values.iter()) {
+            *a *= m;
+        }
+        let a_comm = sparse_commit::<P::G1>(&self.lagrange_comms, &indexes, &a_values);
+        let q_a_comm = sparse_commit::<P::G1>(&self.quotient_comms, &indexes, &a_values);
+        let a_zero_comm = sparse_commit::<P::G1>(&self.lagrange_zero_comms, &indexes, &a_values);
+        let p_a_comm = sparse_commit::<P::G1>(&self.lagrange_shifted_comms, &indexes, &a_values);
+        // A(0) = \sum A_j * L_j(0) = \sum A_j / N
+        let a_eval_zero = a_values
+            .iter()
+            .sum::<P::ScalarField>()
+            .mul(self.vk.domain.size_inv);
+
+        let mut b_values = f_table.0.iter().map(|f| beta.add(f)).collect::<Vec<_>>();
+        batch_inversion(&mut b_values);
+        let b_poly = DensePolynomial::from_coefficients_vec(domain.ifft(&b_values));
+        let b_zero_poly =
+            DensePolynomial::from_coefficients_slice(b_poly.coeffs().get(1..).unwrap_or(&[]));
+
+        // Q_B(X) = (B(X) * (f(X) + β) - 1) / Z_K(X)
+        let f_plus_beta = f_poly
+            .clone()
+            .add(DensePolynomial::from_coefficients_vec(vec![beta]));
+        let numerator = b_poly
+            .mul(&f_plus_beta)
+            .sub(&DensePolynomial::from_coefficients_vec(vec![
+                P::ScalarField::one(),
+            ]));
+        let (q_b_poly, _) = numerator.divide_by_vanishing_poly(domain).unwrap();
+
+        let b_zero_comm = kzg_comm_scheme.commit(&b_zero_poly).unwrap();
+        let q_b_comm = kzg_comm_scheme.commit(&q_b_poly).unwrap();
+        // [P] = [B_0(τ) * τ^{D - n + 2}], B_0 has degree at most n - 2.
+        let shift = self.max_degree - size + 2;
+        let p_bases = P::G1::normalize_batch(
+            &kzg_comm_scheme.0.g1[shift..shift + b_zero_poly.coeffs().len()],
+        );
+        let p_comm = P::G1::msm_unchecked(&p_bases, b_zero_poly.coeffs());
+
+        for comm in [
+            &a_comm,
+            &q_a_comm,
+            &a_zero_comm,
+            &b_zero_comm,
+            &q_b_comm,
+            &p_comm,
+            &p_a_comm,
+        ] {
+            transcript.append_commitment(comm);
+        }
+
+        // 3.Get challenge gamma, open B_0(X), f(X) and Q_B(X) at gamma.
+        let gamma =
+            <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"gamma");
+        let (opening_witness, evaluations) = kzg_comm_scheme
+            .multi_open(
+                &[b_zero_poly, f_poly, q_b_poly],
+                &[b_zero_comm, f_comm, q_b_comm],
+                &[vec![gamma], vec![gamma], vec![gamma]],
+                &mut transcript,
+            )
+            .unwrap();
+
+        CqProof {
+            f_comm,
+            m_comm,
+            a_comm,
+            q_a_comm,
+            a_zero_comm,
+            b_zero_comm,
+            q_b_comm,
+            p_comm,
+            p_a_comm,
+            a_eval_zero,
+            b_zero_eval_gamma: evaluations[0][0],
+            f_eval_gamma: evaluations[1][0],
+            opening_witness,
+            size,
+        }
+    }
+}
+
+impl<G: CurveGroup> CqProof<G> {
+    pub fn verify<P: Pairing<G1 = G, ScalarField = G::ScalarField>>(
+        &self,
+        vk: &CqVerifierKey<P>,
+    ) -> bool {
+        // K is rebuilt from its size, every other field of a domain would be trusted otherwise.
+        let domain = match Radix2EvaluationDomain::<G::ScalarField>::new(self.size) {
+            Some(domain) if domain.size() == self.size => domain,
+            _ => return false,
+        };
+        let size = domain.size();
+        let table_size = vk.domain.size();
+        if size < 2 || size > table_size {
+            return false;
+        }
+        let g2_shift = match vk.g2_shifts.get(domain.log_size_of_group as usize - 1) {
+            Some(g2_shift) => *g2_shift,
+            None => return false,
+        };
+
+        let mut transcript = Transcript::new(b"cq");
+        transcript.append_commitment(&vk.t_comm);
+        transcript.append_u64(b"size", size as u64);
+        transcript.append_commitment(&self.f_comm);
+        transcript.append_commitment(&self.m_comm);
+        let beta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"beta");
+        for comm in [
+            &self.a_comm,
+            &self.q_a_comm,
+            &self.a_zero_comm,
+            &self.b_zero_comm,
+            &self.q_b_comm,
+            &self.p_comm,
+            &self.p_a_comm,
+        ] {
+            transcript.append_commitment(comm);
+        }
+        let gamma = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"gamma");
+
+        // 1.A(X) * (T(X) + β) - m(X) = Q_A(X) * Z_V(X)
+        let a_is_ok = P::multi_pairing(
+            [
+                self.a_comm,
+                self.q_a_comm.neg(),
+                self.a_comm.mul(beta).sub(self.m_comm),
+            ],
+            [vk.t_comm, vk.z_v_comm, vk.vk.g2],
+        )
+        .is_zero();
+
+        // 2.A(X) - A(0) = A_0(X) * X
+        let a_zero_is_ok = P::multi_pairing(
+            [
+                self.a_comm.sub(vk.vk.g1.mul(self.a_eval_zero)),
+                self.a_zero_comm.neg(),
+            ],
+            [vk.vk.g2, vk.vk.g2_tau],
+        )
+        .is_zero();
+
+        // 3.A(X) * X^{D - N + 1} = P_A(X), so A has degree at most N - 1, otherwise A(X) + c * Z_V(X)
+        // passes the checks above with any A(0) - c.
+        let a_degree_is_ok =
+            P::multi_pairing([self.a_comm, self.p_a_comm.neg()], [vk.a_shift, vk.vk.g2]).is_zero();
+
+        // 4.B_0(X) * X^{D - n + 2} = P(X), so B_0 has degree at most n - 2.
+        let b_degree_is_ok =
+            P::multi_pairing([self.b_zero_comm, self.p_comm.neg()], [g2_shift, vk.vk.g2]).is_zero();
+
+        if !(a_is_ok && a_zero_is_ok && a_degree_is_ok && b_degree_is_ok) {
+            return false;
+        }
+
+        // 5.B(γ) * (f(γ) + β) - 1 = Q_B(γ) * Z_K(γ), where B(0) = N * A(0) / n
+        // as \sum_K B_i = \sum_V A_j.
+        let b_eval_zero = self
+            .a_eval_zero
+            .mul(G::ScalarField::from(table_size as u64))
+            .mul(domain.size_inv);
+        let b_eval_gamma = self.b_zero_eval_gamma.mul(gamma).add(b_eval_zero);
+        let z_k_eval_gamma_inv = match domain.evaluate_vanishing_polynomial(gamma).inverse() {
+            Some(inv) => inv,
+            None => return false,
+        };
+        let q_b_eval_gamma = b_eval_gamma
+            .mul(self.f_eval_gamma.add(beta))
+            .sub(G::ScalarField::one())
+            .mul(z_k_eval_gamma_inv);
+
+        vk.vk
+            .multi_verify(
+                &[self.b_zero_comm, self.f_comm, self.q_b_comm],
+                &[vec![gamma], vec![gamma], vec![gamma]],
+                &[
+                    vec![self.b_zero_eval_gamma],
+                    vec![self.f_eval_gamma],
+                    vec![q_b_eval_gamma],
+                ],
+                &self.opening_witness,
+                &mut transcript,
+            )
+            .is_ok()
+    }
+}
@@ -0,0 +1,75 @@
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use kzg::evm::{encode_field, encode_g1, EvmField};
+
+use crate::{logup::LogUpProof, verifier::PlookUpProof};
+
+impl<P: SWCurveConfig> PlookUpProof<Projective<P>>
+where
+    P::BaseField: EvmField,
+    P::ScalarField: EvmField,
+{
+    /// Encodes the proof for the Ethereum precompiles, see `kzg::evm`:
+    ///
+    /// f_comm | t_comm | h1_comm | h2_comm | z_comm | q_comm |
+    /// f(ζ) | t(ζ) | t(ζω) | h1(ζ) | h1(ζω) | h2(ζω) | z(ζω) | comm_w | comm_w'
+    ///
+    /// The domain size is not part of the proof, the verifier takes it from the table key.
+    pub fn to_evm_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        for comm in [
+            &self.f_comm,
+            &self.t_comm,
+            &self.h1_comm,
+            &self.h2_comm,
+            &self.z_comm,
+            &self.q_comm,
+        ] {
+            buf.extend_from_slice(&encode_g1(comm));
+        }
+        let evals = &self.evaluations;
+        for eval in [
+            &evals.f_eval_zeta,
+            &evals.t_eval_zeta,
+            &evals.t_eval_zeta_omega,
+            &evals.h1_eval_zeta,
+            &evals.h1_eval_zeta_omega,
+            &evals.h2_eval_zeta_omega,
+            &evals.z_eval_zeta_omega,
+        ] {
+            buf.extend_from_slice(&encode_field(eval));
+        }
+        buf.extend_from_slice(&self.opening_witness.to_evm_bytes());
+        buf
+    }
+}
+
+impl<P: SWCurveConfig> LogUpProof<Projective<P>>
+where
+    P::BaseField: EvmField,
+    P::ScalarField: EvmField,
+{
+    /// f_comm | t_comm | m_comm | s_comm | q_comm | f(ζ) | t(ζ) | S(ζω) | comm_w | comm_w'
+    ///
+    /// The domain size is not part of the proof, the verifier takes it from the table key.
+    pub fn to_evm_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        for comm in [
+            &self.f_comm,
+            &self.t_comm,
+            &self.m_comm,
+            &self.s_comm,
+            &self.q_comm,
+        ] {
+            buf.extend_from_slice(&encode_g1(comm));
+        }
+        for eval in [
+            &self.f_eval_zeta,
+            &self.t_eval_zeta,
+            &self.s_eval_zeta_omega,
+        ] {
+            buf.extend_from_slice(&encode_field(eval));
+        }
+        buf.extend_from_slice(&self.opening_witness.to_evm_bytes());
+        buf
+    }
+}
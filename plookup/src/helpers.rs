@@ -1,12 +1,12 @@
 use std::ops::{Add, Mul, Sub};
 
+use ark_ec::CurveGroup;
 use ark_ff::PrimeField;
 use ark_poly::{
-    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
-    Radix2EvaluationDomain,
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
 };
 
-use crate::table::SampleTable;
+use crate::{table::SampleTable, verifier::PlookUpEvaluations};
 
 pub fn compute_n_lagrange_poly<F: PrimeField, E: EvaluationDomain<F>>(
     domain: &E,
@@ -18,6 +18,41 @@ pub fn compute_n_lagrange_poly<F: PrimeField, E: EvaluationDomain<F>>(
     DensePolynomial::from_coefficients_vec(evals)
 }
 
+/// L_n(ζ) = ω^n * Z_H(ζ) / (|H| * (ζ - ω^n)), without interpolating L_n(X).
+pub fn evaluate_n_lagrange_poly<F: PrimeField>(
+    domain: &Radix2EvaluationDomain<F>,
+    n: usize,
+    zeta: &F,
+) -> F {
+    let omega_n = domain.element(n);
+    if zeta == &omega_n {
+        return F::one();
+    }
+    omega_n
+        .mul(domain.evaluate_vanishing_polynomial(*zeta))
+        .mul(domain.size_inv)
+        .mul(zeta.sub(&omega_n).inverse().unwrap())
+}
+
+/// Compresses the columns of a table into the single column \sum \theta^i * c_i.
+pub fn compress_columns<F: PrimeField>(columns: &[SampleTable<F>], theta: &F) -> SampleTable<F> {
+    let mut compressed = vec![F::zero(); columns[0].size()];
+    for column in columns.iter().rev() {
+        for (x, c) in compressed.iter_mut().zip(column.0.iter()) {
+            *x = x.mul(theta).add(c);
+        }
+    }
+    SampleTable::from_scalar(compressed)
+}
+
+/// Compresses the commitments of the columns the same way as `compress_columns`.
+pub fn compress_commitments<G: CurveGroup>(comms: &[G], theta: &G::ScalarField) -> G {
+    comms
+        .iter()
+        .rev()
+        .fold(G::zero(), |acc, comm| acc.mul(theta).add(comm))
+}
+
 pub fn compute_t_poly<F: PrimeField>(
     z_poly: &DensePolynomial<F>,
     f_poly: &DensePolynomial<F>,
@@ -27,19 +62,23 @@ pub fn compute_t_poly<F: PrimeField>(
     domain: &Radix2EvaluationDomain<F>,
     beta: &F,
     gamma: &F,
+    alpha: &F,
 ) -> DensePolynomial<F> {
-    let domain_4n = Radix2EvaluationDomain::<F>::new(domain.size() * 4).unwrap();
-    let group_4n = domain_4n.elements();
+    // The witness polynomials have degree at most |H| + 2 with blinding, so the numerator has
+    // degree at most 3|H| + 7, shifting by `shift` on the extended domain is multiplying by ω.
+    let domain_ext = Radix2EvaluationDomain::<F>::new(domain.size() * 3 + 8).unwrap();
+    let group_ext = domain_ext.elements();
+    let shift = domain_ext.size() / domain.size();
     let gn = domain.elements().last().unwrap();
     let l1_poly = compute_n_lagrange_poly(domain, 0);
     let ln_poly = compute_n_lagrange_poly(domain, domain.size() - 1);
-    let mut h1_evals = domain_4n.fft(&h1_poly);
-    let mut h2_evals = domain_4n.fft(&h2_poly);
-    let ln_evals = domain_4n.fft(&ln_poly);
-    let mut z_evals = domain_4n.fft(&z_poly);
-    let f_evals = domain_4n.fft(&f_poly);
-    let mut t_evals = domain_4n.fft(&t_poly);
-    for i in 0..4 {
+    let mut h1_evals = domain_ext.fft(&h1_poly);
+    let mut h2_evals = domain_ext.fft(&h2_poly);
+    let ln_evals = domain_ext.fft(&ln_poly);
+    let mut z_evals = domain_ext.fft(&z_poly);
+    let f_evals = domain_ext.fft(&f_poly);
+    let mut t_evals = domain_ext.fft(&t_poly);
+    for i in 0..shift {
         h1_evals.push(h1_evals[i]);
         h2_evals.push(h2_evals[i]);
         t_evals.push(t_evals[i]);
@@ -50,25 +89,19 @@ pub fn compute_t_poly<F: PrimeField>(
         .add(ln_poly.clone())
         .mul(&z_poly.sub(&DensePolynomial::from_coefficients_vec(vec![F::one()])));
 
-    let (_, remainder) = term1_poly.divide_by_vanishing_poly(*domain).unwrap();
-    assert!(remainder.is_empty());
-
     let mut term2_evals = vec![];
-    for i in 0..domain_4n.size() {
-        let eval = ln_evals[i].mul(h1_evals[i].sub(h2_evals[i + 4]));
+    for i in 0..domain_ext.size() {
+        let eval = ln_evals[i].mul(h1_evals[i].sub(h2_evals[i + shift]));
         term2_evals.push(eval);
     }
-    let term2_coefs = domain_4n.ifft(&term2_evals);
+    let term2_coefs = domain_ext.ifft(&term2_evals);
     let term2_poly = DensePolynomial::from_coefficients_vec(term2_coefs);
 
-    let (_, remainder) = term2_poly.divide_by_vanishing_poly(*domain).unwrap();
-    assert!(remainder.is_empty());
-
     let beta_plus_one = beta.add(&F::one());
     let gamma_mul_beta_plus_one = gamma.mul(&beta_plus_one);
-    let term3_evals = (0..domain_4n.size())
+    let term3_evals = (0..domain_ext.size())
         .into_iter()
-        .zip(group_4n)
+        .zip(group_ext)
         .map(|(i, ele)| {
             let tmp = ele.sub(gn);
 
@@ -77,100 +110,122 @@ pub fn compute_t_poly<F: PrimeField>(
             term1.mul_assign(
                 gamma_mul_beta_plus_one
                     .add(t_evals[i])
-                    .add(t_evals[i + 4].mul(beta)),
+                    .add(t_evals[i + shift].mul(beta)),
             );
 
-            let mut term2 = tmp.mul(z_evals[i + 4]);
+            let mut term2 = tmp.mul(z_evals[i + shift]);
             term2.mul_assign(
                 gamma_mul_beta_plus_one
                     .add(h1_evals[i])
-                    .add(h1_evals[i + 4].mul(beta)),
+                    .add(h1_evals[i + shift].mul(beta)),
             );
             term2.mul_assign(
                 gamma_mul_beta_plus_one
                     .add(h2_evals[i])
-                    .add(h2_evals[i + 4].mul(beta)),
+                    .add(h2_evals[i + shift].mul(beta)),
             );
 
             term1.sub(term2)
         })
         .collect::<Vec<F>>();
 
-    let term3_coefs = domain_4n.ifft(&term3_evals);
+    let term3_coefs = domain_ext.ifft(&term3_evals);
     let term3_poly = DensePolynomial::from_coefficients_vec(term3_coefs);
 
-    let (_, remainder) = term3_poly.divide_by_vanishing_poly(*domain).unwrap();
-    assert!(remainder.is_empty());
-
-    let term_sum = term1_poly.add(term2_poly).add(term3_poly);
-    let (t_poly, remainder) = term_sum.divide_by_vanishing_poly(*domain).unwrap();
-    assert!(remainder.is_empty());
+    // The remainder vanishes only if f is a subset of t, the verifier catches it otherwise.
+    let term_sum = term1_poly
+        .add(term2_poly.mul(*alpha))
+        .add(term3_poly.mul(alpha.square()));
+    let (t_poly, _) = term_sum.divide_by_vanishing_poly(*domain).unwrap();
 
     t_poly
 }
 
-pub fn compute_r_poly<F: PrimeField>(
-    z_poly: &DensePolynomial<F>,
-    h1_poly: &DensePolynomial<F>,
-    h2_poly: &DensePolynomial<F>,
-    quotient_poly: &DensePolynomial<F>,
-    f_poly_eval_zeta: &F,
-    t_poly_eval_zeta: &F,
-    t_poly_eval_zeta_omega: &F,
+/// Coefficients of the linearization
+/// r(X) = c_z * z(X) + c_h1 * h1(X) + c_h2 * h2(X) + c_q * q(X) + c,
+/// once the evaluations at ζ and ζω are fixed. r(ζ) = 0 iff the quotient identity holds at ζ.
+#[derive(Debug, Clone, Copy)]
+pub struct LinearizationCoefficients<F: PrimeField> {
+    pub z: F,
+    pub h1: F,
+    pub h2: F,
+    pub quotient: F,
+    pub constant: F,
+}
+
+pub fn compute_linearization_coefficients<F: PrimeField>(
+    evaluations: &PlookUpEvaluations<F>,
     zeta: &F,
+    alpha: &F,
     beta: &F,
     gamma: &F,
-    zeta_omega: &F,
     domain: &Radix2EvaluationDomain<F>,
-) -> DensePolynomial<F> {
-    let h1_poly_eval_zeta = h1_poly.evaluate(&zeta);
-    let h1_poly_eval_zeta_omega = h1_poly.evaluate(&zeta_omega);
-    let h2_poly_eval_zeta_omega = h2_poly.evaluate(&zeta_omega);
-    let z_poly_eval_zeta_omega = z_poly.evaluate(&zeta_omega);
-    let gn = domain.elements().last().unwrap();
+) -> LinearizationCoefficients<F> {
+    let gn = domain.element(domain.size() - 1);
     let beta_plus_one = beta.add(&F::one());
     let gamma_mul_beta_plus_one = gamma.mul(&beta_plus_one);
-    let l1_poly = compute_n_lagrange_poly(domain, 0);
-    let ln_poly = compute_n_lagrange_poly(domain, domain.size() - 1);
-    let l1_poly_eval_zeta = l1_poly.evaluate(zeta);
-    let ln_poly_eval_zeta = ln_poly.evaluate(zeta);
+    let alpha_square = alpha.square();
+    let l1_eval_zeta = evaluate_n_lagrange_poly(domain, 0, zeta);
+    let ln_eval_zeta = evaluate_n_lagrange_poly(domain, domain.size() - 1, zeta);
+    let zeta_sub_gn = zeta.sub(&gn);
 
-    let term1 = z_poly
-        .sub(&DensePolynomial::from_coefficients_vec(vec![F::one()]))
-        .mul(l1_poly_eval_zeta.add(ln_poly_eval_zeta));
+    // term1 = (z(X) - 1) * (L1(ζ) + Ln(ζ))
+    let term1 = l1_eval_zeta.add(ln_eval_zeta);
 
-    let term2 = z_poly
-        .mul(zeta.sub(&gn))
+    // term2 = \alpha * Ln(ζ) * (h1(X) - h2(ζω))
+    let term2 = alpha.mul(ln_eval_zeta);
+
+    // term3 = \alpha^2 * (ζ - g^n) * (z(X) * (\beta + 1) * (\gamma + f(ζ)) * (\gamma * (\beta + 1) + t(ζ) + \beta * t(ζω))
+    //         - z(ζω) * (\gamma * (\beta + 1) + h1(ζ) + \beta * h1(ζω)) * (\gamma * (\beta + 1) + h2(X) + \beta * h2(ζω)))
+    let term3_z = alpha_square
+        .mul(zeta_sub_gn)
         .mul(beta_plus_one)
-        .mul(gamma.add(f_poly_eval_zeta))
+        .mul(gamma.add(&evaluations.f_eval_zeta))
         .mul(
             gamma_mul_beta_plus_one
-                .add(t_poly_eval_zeta)
-                .add(beta.mul(t_poly_eval_zeta_omega)),
+                .add(evaluations.t_eval_zeta)
+                .add(beta.mul(&evaluations.t_eval_zeta_omega)),
         );
+    let term3_h2 = alpha_square
+        .mul(zeta_sub_gn)
+        .mul(evaluations.z_eval_zeta_omega)
+        .mul(
+            gamma_mul_beta_plus_one
+                .add(evaluations.h1_eval_zeta)
+                .add(beta.mul(&evaluations.h1_eval_zeta_omega)),
+        )
+        .neg();
 
-    let term3 = h2_poly.add(&DensePolynomial::from_coefficients_vec(vec![
-        gamma_mul_beta_plus_one.add(beta.mul(&h2_poly_eval_zeta_omega)),
-    ]));
-    let term3 = term3.mul(zeta.sub(&gn).mul(z_poly_eval_zeta_omega)).mul(
-        gamma_mul_beta_plus_one
-            .add(h1_poly_eval_zeta)
-            .add(beta.mul(&h1_poly_eval_zeta_omega)),
-    );
-
-    let ln_poly_eval_zeta = ln_poly.evaluate(zeta);
-    let term4 = h1_poly
-        .sub(&DensePolynomial::from_coefficients_vec(vec![
-            h2_poly_eval_zeta_omega,
+    LinearizationCoefficients {
+        z: term1.add(term3_z),
+        h1: term2,
+        h2: term3_h2,
+        quotient: domain.evaluate_vanishing_polynomial(*zeta).neg(),
+        constant: term1
+            .neg()
+            .sub(term2.mul(evaluations.h2_eval_zeta_omega))
+            .add(
+                term3_h2
+                    .mul(gamma_mul_beta_plus_one.add(beta.mul(&evaluations.h2_eval_zeta_omega))),
+            ),
+    }
+}
+
+pub fn compute_r_poly<F: PrimeField>(
+    z_poly: &DensePolynomial<F>,
+    h1_poly: &DensePolynomial<F>,
+    h2_poly: &DensePolynomial<F>,
+    quotient_poly: &DensePolynomial<F>,
+    coefficients: &LinearizationCoefficients<F>,
+) -> DensePolynomial<F> {
+    z_poly
+        .mul(coefficients.z)
+        .add(h1_poly.mul(coefficients.h1))
+        .add(h2_poly.mul(coefficients.h2))
+        .add(quotient_poly.mul(coefficients.quotient))
+        .add(DensePolynomial::from_coefficients_vec(vec![
+            coefficients.constant,
         ]))
-        .mul(ln_poly_eval_zeta);
-
-    let r_poly = term1
-        .add(term2)
-        .sub(&term3)
-        .add(term4)
-        .sub(&quotient_poly.mul(domain.vanishing_polynomial().evaluate(&zeta)));
-    r_poly
 }
 
 pub fn compute_z_poly<F: PrimeField>(
@@ -212,8 +267,6 @@ pub fn compute_z_poly<F: PrimeField>(
         let denominator_inv = denominator.inverse().unwrap();
         z_evals.push(numerator.mul(&denominator_inv));
     }
-    assert_eq!(z_evals.last().unwrap().to_owned(), F::one());
-
     let z_coefs = domain.ifft(&z_evals);
     let z_poly = DensePolynomial::from_coefficients_vec(z_coefs);
     z_poly
@@ -1,7 +1,10 @@
+pub mod cq;
+pub mod evm;
 pub mod helpers;
+pub mod logup;
 pub mod prover;
 pub mod table;
+pub mod tables;
 #[cfg(test)]
 pub mod test;
-pub mod transcripts;
 pub mod verifier;
@@ -0,0 +1,352 @@
+//! LogUp, the lookup argument from the logarithmic derivative
+//! \sum 1 / (α - f_i) = \sum m_j / (α - t_j), where m_j counts the lookups of t_j.
+//!
+//! The running sum S(X) adds m_i / (α - t_i) - 1 / (α - f_i) from one element of H to the next,
+//! so it wraps around H iff both sums are equal:
+//! (S(ωX) - S(X)) * (α - f(X)) * (α - t(X)) = m(X) * (α - f(X)) - (α - t(X)) on H.
+//!
+//! https://eprint.iacr.org/2022/1530.pdf
+
+use std::{
+    collections::HashMap,
+    ops::{Add, Mul},
+};
+
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{batch_inversion, PrimeField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
+    Radix2EvaluationDomain,
+};
+use ark_std::rand::Rng;
+use kzg::{
+    accumulator::PairingAccumulator,
+    commitment::{KZGCommitmentScheme, KZGMultiOpeningProof},
+    srs::VerifierKey,
+    transcripts::GlobalTranscript,
+};
+use merlin::Transcript;
+
+use crate::{
+    table::{PreProcessedTable, SampleTable},
+    verifier::TableVerifierKey,
+};
+
+pub struct LogUpProof<G: CurveGroup> {
+    pub f_comm: G,
+    pub t_comm: G,
+    pub m_comm: G,
+    pub s_comm: G,
+    pub q_comm: G,
+    pub f_eval_zeta: G::ScalarField,
+    pub t_eval_zeta: G::ScalarField,
+    pub s_eval_zeta_omega: G::ScalarField,
+    pub opening_witness: KZGMultiOpeningProof<G>,
+}
+
+/// Coefficients of the linearization r(X) = c_s * S(X) + c_m * m(X) + c_q * q(X) + c.
+struct LinearizationCoefficients<F: PrimeField> {
+    s: F,
+    m: F,
+    quotient: F,
+    constant: F,
+}
+
+/// r(X) = (S(ζω) - S(X)) * (α - f(ζ)) * (α - t(ζ)) - m(X) * (α - f(ζ)) + (α - t(ζ)) - q(X) * Z_H(ζ)
+fn compute_linearization_coefficients<F: PrimeField>(
+    f_eval_zeta: &F,
+    t_eval_zeta: &F,
+    s_eval_zeta_omega: &F,
+    zeta: &F,
+    alpha: &F,
+    domain: &Radix2EvaluationDomain<F>,
+) -> LinearizationCoefficients<F> {
+    let alpha_sub_f = alpha.sub(f_eval_zeta);
+    let alpha_sub_t = alpha.sub(t_eval_zeta);
+    let product = alpha_sub_f.mul(alpha_sub_t);
+
+    LinearizationCoefficients {
+        s: product.neg(),
+        m: alpha_sub_f.neg(),
+        quotient: domain.evaluate_vanishing_polynomial(*zeta).neg(),
+        constant: s_eval_zeta_omega.mul(product).add(alpha_sub_t),
+    }
+}
+
+/// m_j is the number of lookups of t_j, duplicated entries of t are counted once.
+fn compute_multiplicities<F: PrimeField>(
+    f_table: &SampleTable<F>,
+    t_table: &SampleTable<F>,
+) -> SampleTable<F> {
+    let mut indexes = HashMap::new();
+    for (j, t) in t_table.0.iter().enumerate() {
+        indexes.entry(*t).or_insert(j);
+    }
+
+    let mut multiplicities = vec![0u64; t_table.size()];
+    for f in f_table.0.iter() {
+        match indexes.get(f) {
+            Some(j) => multiplicities[*j] += 1,
+            None => panic!("Current prover's table is not a subset of look up table"),
+        }
+    }
+
+    SampleTable::from_u64(multiplicities)
+}
+
+/// S_0 = 0, S_{i+1} = S_i + m_i / (α - t_i) - 1 / (α - f_i).
+fn compute_running_sum<F: PrimeField>(
+    f_table: &SampleTable<F>,
+    t_table: &SampleTable<F>,
+    m_table: &SampleTable<F>,
+    alpha: &F,
+) -> SampleTable<F> {
+    let mut f_inverses = f_table.0.iter().map(|f| alpha.sub(f)).collect::<Vec<_>>();
+    let mut t_inverses = t_table.0.iter().map(|t| alpha.sub(t)).collect::<Vec<_>>();
+    batch_inversion(&mut f_inverses);
+    batch_inversion(&mut t_inverses);
+
+    let mut s_evals = vec![F::zero()];
+    for i in 0..t_table.size() - 1 {
+        let term = m_table.0[i].mul(t_inverses[i]).sub(f_inverses[i]);
+        s_evals.push(s_evals[i].add(term));
+    }
+
+    SampleTable::from_scalar(s_evals)
+}
+
+fn compute_quotient_poly<F: PrimeField>(
+    f_poly: &DensePolynomial<F>,
+    t_poly: &DensePolynomial<F>,
+    m_poly: &DensePolynomial<F>,
+    s_poly: &DensePolynomial<F>,
+    alpha: &F,
+    domain: &Radix2EvaluationDomain<F>,
+) -> DensePolynomial<F> {
+    // The numerator has degree at most 3|H| - 3, shifting by `shift` on the extended domain
+    // is multiplying by ω.
+    let domain_ext = Radix2EvaluationDomain::<F>::new(domain.size() * 3).unwrap();
+    let shift = domain_ext.size() / domain.size();
+    let f_evals = domain_ext.fft(f_poly);
+    let t_evals = domain_ext.fft(t_poly);
+    let m_evals = domain_ext.fft(m_poly);
+    let mut s_evals = domain_ext.fft(s_poly);
+    for i in 0..shift {
+        s_evals.push(s_evals[i]);
+    }
+
+    let numerator_evals = (0..domain_ext.size())
+        .map(|i| {
+            let alpha_sub_f = alpha.sub(f_evals[i]);
+            let alpha_sub_t = alpha.sub(t_evals[i]);
+
+            s_evals[i + shift]
+                .sub(s_evals[i])
+                .mul(alpha_sub_f)
+                .mul(alpha_sub_t)
+                .sub(m_evals[i].mul(alpha_sub_f))
+                .add(alpha_sub_t)
+        })
+        .collect::<Vec<F>>();
+    let numerator_poly = DensePolynomial::from_coefficients_vec(domain_ext.ifft(&numerator_evals));
+
+    // The remainder vanishes only if f is a subset of t, the verifier catches it otherwise.
+    let (quotient_poly, _) = numerator_poly.divide_by_vanishing_poly(*domain).unwrap();
+    quotient_poly
+}
+
+pub(super) fn prove<P: Pairing>(
+    mut f_table: SampleTable<P::ScalarField>,
+    t_preprocess_table: PreProcessedTable<P::G1>,
+    domain: &Radix2EvaluationDomain<P::ScalarField>,
+    kzg_comm_scheme: &KZGCommitmentScheme<P>,
+) -> LogUpProof<P::G1> {
+    let mut transcript = Transcript::new(b"logup");
+    transcript.append_u64(b"size", domain.size);
+
+    let (t_poly, t_comm, t_table) = (
+        t_preprocess_table.poly,
+        t_preprocess_table.comm,
+        t_preprocess_table.table,
+    );
+    transcript.append_commitment(&t_comm);
+
+    // 1.Pad f with the first entry of t, count the multiplicities and commit them.
+    f_table.0.resize(domain.size(), t_table.0[0]);
+    let m_table = compute_multiplicities(&f_table, &t_table);
+
+    let f_preprocess_table = f_table.preprocess(kzg_comm_scheme, domain);
+    transcript.append_commitment(&f_preprocess_table.comm);
+    let m_preprocess_table = m_table.preprocess(kzg_comm_scheme, domain);
+    transcript.append_commitment(&m_preprocess_table.comm);
+
+    // 2.Get challenge alpha, compute the running sum S(X) and commit it.
+    let alpha = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"alpha");
+    let s_table = compute_running_sum(&f_table, &t_table, &m_table, &alpha);
+    let s_preprocess_table = s_table.preprocess(kzg_comm_scheme, domain);
+    transcript.append_commitment(&s_preprocess_table.comm);
+
+    // 3.Compute the quotient polynomial and commit it.
+    let (f_poly, m_poly, s_poly) = (
+        f_preprocess_table.poly,
+        m_preprocess_table.poly,
+        s_preprocess_table.poly,
+    );
+    let quotient_poly = compute_quotient_poly(&f_poly, &t_poly, &m_poly, &s_poly, &alpha, domain);
+    let q_comm = kzg_comm_scheme.commit(&quotient_poly).unwrap();
+    transcript.append_commitment(&q_comm);
+
+    let zeta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"zeta");
+    let zeta_omega = zeta.mul(&domain.group_gen);
+    let f_eval_zeta = f_poly.evaluate(&zeta);
+    let t_eval_zeta = t_poly.evaluate(&zeta);
+    let s_eval_zeta_omega = s_poly.evaluate(&zeta_omega);
+
+    // 4.Compute the linearization r(X), the verifier rebuilds its commitment.
+    let coefficients = compute_linearization_coefficients(
+        &f_eval_zeta,
+        &t_eval_zeta,
+        &s_eval_zeta_omega,
+        &zeta,
+        &alpha,
+        domain,
+    );
+    let r_poly = s_poly
+        .mul(coefficients.s)
+        .add(m_poly.mul(coefficients.m))
+        .add(quotient_poly.mul(coefficients.quotient))
+        .add(DensePolynomial::from_coefficients_vec(vec![
+            coefficients.constant,
+        ]));
+    let r_comm = s_preprocess_table.comm.mul(coefficients.s)
+        + m_preprocess_table.comm.mul(coefficients.m)
+        + q_comm.mul(coefficients.quotient)
+        + kzg_comm_scheme.0.g1[0].mul(coefficients.constant);
+
+    // 5.Open f, t and r at zeta and S at zeta * omega with a single proof, r(zeta) = 0.
+    let (opening_witness, _) = kzg_comm_scheme
+        .multi_open(
+            &[f_poly, t_poly, s_poly, r_poly],
+            &[
+                f_preprocess_table.comm,
+                t_comm,
+                s_preprocess_table.comm,
+                r_comm,
+            ],
+            &[vec![zeta], vec![zeta], vec![zeta_omega], vec![zeta]],
+            &mut transcript,
+        )
+        .unwrap();
+
+    LogUpProof {
+        f_comm: f_preprocess_table.comm,
+        t_comm,
+        m_comm: m_preprocess_table.comm,
+        s_comm: s_preprocess_table.comm,
+        q_comm,
+        f_eval_zeta,
+        t_eval_zeta,
+        s_eval_zeta_omega,
+        opening_witness,
+    }
+}
+
+impl<G: CurveGroup> LogUpProof<G> {
+    /// The domain is rebuilt from `table_vk`, the proof does not carry it.
+    pub fn verify<P: Pairing<G1 = G, ScalarField = G::ScalarField>>(
+        &self,
+        vk: &VerifierKey<P>,
+        table_vk: &TableVerifierKey<G>,
+    ) -> bool {
+        let domain = match table_vk.domain() {
+            Some(domain) if table_vk.pins(&self.t_comm) => domain,
+            _ => return false,
+        };
+
+        let (mut transcript, comms, points, evaluations) = self.replay_transcript(&vk.g1, &domain);
+        vk.multi_verify(
+            &comms,
+            &points,
+            &evaluations,
+            &self.opening_witness,
+            &mut transcript,
+        )
+        .is_ok()
+    }
+
+    /// Same as `verify`, but the pairing check is deferred to `accumulator`.
+    pub fn accumulate<P: Pairing<G1 = G, ScalarField = G::ScalarField>, R: Rng>(
+        &self,
+        vk: &VerifierKey<P>,
+        table_vk: &TableVerifierKey<G>,
+        accumulator: &mut PairingAccumulator<G>,
+        rng: &mut R,
+    ) -> bool {
+        let domain = match table_vk.domain() {
+            Some(domain) if table_vk.pins(&self.t_comm) => domain,
+            _ => return false,
+        };
+
+        let (mut transcript, comms, points, evaluations) = self.replay_transcript(&vk.g1, &domain);
+        vk.multi_accumulate(
+            &comms,
+            &points,
+            &evaluations,
+            &self.opening_witness,
+            &mut transcript,
+            accumulator,
+            rng,
+        )
+        .is_ok()
+    }
+
+    /// Replays the transcript of the prover up to the opening, and returns the opened
+    /// commitments together with their opening points and claimed evaluations.
+    #[allow(clippy::type_complexity)]
+    fn replay_transcript(
+        &self,
+        g1: &G,
+        domain: &Radix2EvaluationDomain<G::ScalarField>,
+    ) -> (
+        Transcript,
+        Vec<G>,
+        Vec<Vec<G::ScalarField>>,
+        Vec<Vec<G::ScalarField>>,
+    ) {
+        let mut transcript = Transcript::new(b"logup");
+        transcript.append_u64(b"size", domain.size);
+        transcript.append_commitment(&self.t_comm);
+        transcript.append_commitment(&self.f_comm);
+        transcript.append_commitment(&self.m_comm);
+        let alpha = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"alpha");
+        transcript.append_commitment(&self.s_comm);
+        transcript.append_commitment(&self.q_comm);
+
+        let zeta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"zeta");
+        let zeta_omega = zeta.mul(&domain.group_gen);
+
+        let coefficients = compute_linearization_coefficients(
+            &self.f_eval_zeta,
+            &self.t_eval_zeta,
+            &self.s_eval_zeta_omega,
+            &zeta,
+            &alpha,
+            domain,
+        );
+        let r_comm = self.s_comm.mul(coefficients.s)
+            + self.m_comm.mul(coefficients.m)
+            + self.q_comm.mul(coefficients.quotient)
+            + g1.mul(coefficients.constant);
+
+        let comms = vec![self.f_comm, self.t_comm, self.s_comm, r_comm];
+        let points = vec![vec![zeta], vec![zeta], vec![zeta_omega], vec![zeta]];
+        let evaluations = vec![
+            vec![self.f_eval_zeta],
+            vec![self.t_eval_zeta],
+            vec![self.s_eval_zeta_omega],
+            vec![G::ScalarField::zero()],
+        ];
+
+        (transcript, comms, points, evaluations)
+    }
+}
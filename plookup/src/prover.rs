@@ -1,27 +1,95 @@
 use ark_ec::pairing::Pairing;
-use ark_ff::Zero;
+use ark_ff::{PrimeField, Zero};
 use ark_poly::{
     univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
     Radix2EvaluationDomain,
 };
-use kzg::commitment::KZGCommitmentScheme;
+use ark_std::rand::Rng;
+use kzg::{commitment::KZGCommitmentScheme, transcripts::GlobalTranscript};
 use merlin::Transcript;
 
 use crate::{
-    helpers::{compute_r_poly, compute_t_poly, compute_z_poly},
+    helpers::{
+        compress_columns, compress_commitments, compute_linearization_coefficients, compute_r_poly,
+        compute_t_poly, compute_z_poly,
+    },
     table::{PreProcessedTable, SampleTable},
-    transcripts::GlobalTranscript,
-    verifier::PlookUpProof,
+    verifier::{MultiColumnPlookUpProof, PlookUpEvaluations, PlookUpProof},
 };
-use std::ops::Mul;
+use std::ops::{Add, Mul};
+
+/// Random polynomials b(X) whose multiples b(X) * Z_H(X) are added to the witness polynomials.
+/// They leave the polynomials unchanged on H, but make their openings uniformly random.
+pub(crate) struct Blinders<F: PrimeField> {
+    pub(crate) f: DensePolynomial<F>,
+    pub(crate) h1: DensePolynomial<F>,
+    pub(crate) h2: DensePolynomial<F>,
+    pub(crate) z: DensePolynomial<F>,
+}
+
+impl<F: PrimeField> Blinders<F> {
+    /// No blinding, the proof is not zero-knowledge.
+    pub(crate) fn none() -> Self {
+        Blinders {
+            f: DensePolynomial::zero(),
+            h1: DensePolynomial::zero(),
+            h2: DensePolynomial::zero(),
+            z: DensePolynomial::zero(),
+        }
+    }
+
+    /// f is opened at a single point, while h1, h2 and z are opened at up to two points
+    /// and through r(X), so they get one more random coefficient, as in PLONK.
+    pub(crate) fn rand<R: Rng>(rng: &mut R) -> Self {
+        Blinders {
+            f: DensePolynomial::rand(1, rng),
+            h1: DensePolynomial::rand(2, rng),
+            h2: DensePolynomial::rand(2, rng),
+            z: DensePolynomial::rand(2, rng),
+        }
+    }
+}
+
+fn blind<F: PrimeField>(
+    poly: DensePolynomial<F>,
+    blinder: &DensePolynomial<F>,
+    domain: &Radix2EvaluationDomain<F>,
+) -> DensePolynomial<F> {
+    poly.add(blinder.mul_by_vanishing_poly(*domain))
+}
 
 pub(super) fn prove<P: Pairing>(
     mut f_table: SampleTable<P::ScalarField>,
     t_preprocess_table: PreProcessedTable<P::G1>,
+    blinders: &Blinders<P::ScalarField>,
+    domain: &Radix2EvaluationDomain<P::ScalarField>,
+    kzg_comm_scheme: &KZGCommitmentScheme<P>,
+) -> PlookUpProof<P::G1> {
+    // 1.Compute f_table sorted by t_table.
+    let sorted_table = f_table.sort_by(&t_preprocess_table.table);
+
+    prove_with_sorted_table(
+        &mut Transcript::new(b"plookup"),
+        f_table,
+        sorted_table,
+        t_preprocess_table,
+        blinders,
+        domain,
+        kzg_comm_scheme,
+    )
+}
+
+/// Runs the protocol from (f, t) sorted by t, which is not checked here,
+/// so a dishonest `sorted_table` produces a proof the verifier rejects.
+pub(crate) fn prove_with_sorted_table<P: Pairing>(
+    transcript: &mut Transcript,
+    f_table: SampleTable<P::ScalarField>,
+    sorted_table: SampleTable<P::ScalarField>,
+    t_preprocess_table: PreProcessedTable<P::G1>,
+    blinders: &Blinders<P::ScalarField>,
     domain: &Radix2EvaluationDomain<P::ScalarField>,
     kzg_comm_scheme: &KZGCommitmentScheme<P>,
 ) -> PlookUpProof<P::G1> {
-    let mut transcript = Transcript::new(b"plookup");
     transcript.append_u64(b"size", domain.size as u64);
 
     let (t_poly, t_comm, t_table) = (
@@ -29,91 +97,197 @@ pub(super) fn prove<P: Pairing>(
         t_preprocess_table.comm,
         t_preprocess_table.table,
     );
-    transcript.append_commitent(&t_comm);
-
-    // 1.Compute f_table sorted by t_table.
-    let sorted_table = f_table.sort_by(&t_table);
+    transcript.append_commitment(&t_comm);
 
+    // The quotient is computed on a coset of a larger domain and the openings divide the
+    // polynomials, both need their coefficients, so committing to the evaluations with
+    // a lagrange basis would not save the IFFT. The blinders b(X) * Z_H(X) are not
+    // spanned by the lagrange basis of H either.
     let f_coefs = domain.ifft(&f_table.0);
-    let f_poly = DensePolynomial::from_coefficients_vec(f_coefs);
-    let f_comm = kzg_comm_scheme.commit(&f_poly);
-    transcript.append_commitent(&f_comm);
+    let f_poly = blind(
+        DensePolynomial::from_coefficients_vec(f_coefs),
+        &blinders.f,
+        domain,
+    );
+    let f_comm = kzg_comm_scheme.commit(&f_poly).unwrap();
+    transcript.append_commitment(&f_comm);
 
     // 2.Compute two helper polynomial(h1, h2) and commit them.
     let n = sorted_table.size() / 2;
     let h1_table = SampleTable::from_scalar(sorted_table.0[..=n].to_vec());
     let h1_coefs = domain.ifft(&h1_table.0);
-    let h1_poly = DensePolynomial::from_coefficients_vec(h1_coefs);
-    let h1_comm = kzg_comm_scheme.commit(&h1_poly);
-    transcript.append_commitent(&h1_comm);
+    let h1_poly = blind(
+        DensePolynomial::from_coefficients_vec(h1_coefs),
+        &blinders.h1,
+        domain,
+    );
+    let h1_comm = kzg_comm_scheme.commit(&h1_poly).unwrap();
+    transcript.append_commitment(&h1_comm);
 
     let h2_table = SampleTable::from_scalar(sorted_table.0[n..].to_vec());
     let h2_coefs = domain.ifft(&h2_table.0);
-    let h2_poly = DensePolynomial::from_coefficients_vec(h2_coefs);
-    let h2_comm = kzg_comm_scheme.commit(&h2_poly);
-    transcript.append_commitent(&h2_comm);
+    let h2_poly = blind(
+        DensePolynomial::from_coefficients_vec(h2_coefs),
+        &blinders.h2,
+        domain,
+    );
+    let h2_comm = kzg_comm_scheme.commit(&h2_poly).unwrap();
+    transcript.append_commitment(&h2_comm);
 
     // 3.Get challenge beta and gamma.
-    let beta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"beta");
-    let gamma = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"gamma");
+    let beta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(transcript, b"beta");
+    let gamma = <Transcript as GlobalTranscript<P::G1>>::get_challenge(transcript, b"gamma");
 
     // 4.Compute z(X) and commit it.
-    let z_poly = compute_z_poly(
-        n, &f_table, &t_table, &h1_table, &h2_table, &gamma, &beta, domain,
+    let z_poly = blind(
+        compute_z_poly(
+            n, &f_table, &t_table, &h1_table, &h2_table, &gamma, &beta, domain,
+        ),
+        &blinders.z,
+        domain,
     );
-    let z_comm = kzg_comm_scheme.commit(&z_poly);
-    transcript.append_commitent(&z_comm);
+    let z_comm = kzg_comm_scheme.commit(&z_poly).unwrap();
+    transcript.append_commitment(&z_comm);
 
-    // 5.Compute quotient polynomial(t(x)).
+    // 5.Compute quotient polynomial(t(x)) and commit it.
+    let alpha = <Transcript as GlobalTranscript<P::G1>>::get_challenge(transcript, b"alpha");
     let quotient_poly = compute_t_poly(
-        &z_poly, &f_poly, &t_poly, &h1_poly, &h2_poly, domain, &beta, &gamma,
+        &z_poly, &f_poly, &t_poly, &h1_poly, &h2_poly, domain, &beta, &gamma, &alpha,
     );
+    let q_comm = kzg_comm_scheme.commit(&quotient_poly).unwrap();
+    transcript.append_commitment(&q_comm);
 
-    let zeta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"zeta");
+    let zeta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(transcript, b"zeta");
     let zeta_omega = zeta.mul(&domain.group_gen);
-    let f_poly_eval_zeta = f_poly.evaluate(&zeta);
-    let t_poly_eval_zeta = t_poly.evaluate(&zeta);
-    let t_poly_eval_zeta_omega = t_poly.evaluate(&zeta_omega);
-
-    // 6.Compute r(X) and commit it.
-    let r_poly = compute_r_poly(
-        &z_poly,
-        &h1_poly,
-        &h2_poly,
-        &quotient_poly,
-        &f_poly_eval_zeta,
-        &t_poly_eval_zeta,
-        &t_poly_eval_zeta_omega,
-        &zeta,
-        &beta,
-        &gamma,
-        &zeta_omega,
-        domain,
-    );
-    assert!(r_poly.evaluate(&zeta) == P::ScalarField::zero());
-    let r_comm = kzg_comm_scheme.commit(&r_poly);
-    transcript.append_commitent(&r_comm);
-
-    // 7.Compute two opening proof polynomial.
-    let v = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"v");
-    let w_zeta = kzg_comm_scheme.batch_prove(
-        &[f_poly, t_poly.clone(), h1_poly.clone(), r_poly],
-        &zeta,
-        &v,
-    );
+    let evaluations = PlookUpEvaluations {
+        f_eval_zeta: f_poly.evaluate(&zeta),
+        t_eval_zeta: t_poly.evaluate(&zeta),
+        t_eval_zeta_omega: t_poly.evaluate(&zeta_omega),
+        h1_eval_zeta: h1_poly.evaluate(&zeta),
+        h1_eval_zeta_omega: h1_poly.evaluate(&zeta_omega),
+        h2_eval_zeta_omega: h2_poly.evaluate(&zeta_omega),
+        z_eval_zeta_omega: z_poly.evaluate(&zeta_omega),
+    };
+
+    // 6.Compute the linearization r(X), the verifier rebuilds its commitment.
+    let coefficients =
+        compute_linearization_coefficients(&evaluations, &zeta, &alpha, &beta, &gamma, domain);
+    let r_poly = compute_r_poly(&z_poly, &h1_poly, &h2_poly, &quotient_poly, &coefficients);
+    let r_comm = z_comm.mul(coefficients.z)
+        + h1_comm.mul(coefficients.h1)
+        + h2_comm.mul(coefficients.h2)
+        + q_comm.mul(coefficients.quotient)
+        + kzg_comm_scheme.0.g1[0].mul(coefficients.constant);
 
-    let w_zeta_omega =
-        kzg_comm_scheme.batch_prove(&[t_poly, h1_poly, h2_poly, z_poly], &zeta_omega, &v);
+    // 7.Open all polynomials at zeta and zeta * omega with a single proof, r(zeta) = 0.
+    let (opening_witness, _) = kzg_comm_scheme
+        .multi_open(
+            &[f_poly, t_poly, h1_poly, h2_poly, z_poly, r_poly],
+            &[f_comm, t_comm, h1_comm, h2_comm, z_comm, r_comm],
+            &[
+                vec![zeta],
+                vec![zeta, zeta_omega],
+                vec![zeta, zeta_omega],
+                vec![zeta_omega],
+                vec![zeta_omega],
+                vec![zeta],
+            ],
+            transcript,
+        )
+        .unwrap();
 
     PlookUpProof {
         f_comm,
         t_comm,
         h1_comm,
         h2_comm,
-        r_comm,
         z_comm,
-        opening_witness_zeta: w_zeta,
-        opening_witness_zeta_omega: w_zeta_omega,
-        domain: domain.to_owned(),
+        q_comm,
+        evaluations,
+        opening_witness,
+    }
+}
+
+/// The columns of the witness are blinded by `f_blinders`, the compressed witness is blinded by
+/// their compression, `blinders.f` is ignored.
+pub(super) fn prove_multi_column<P: Pairing>(
+    mut f_columns: Vec<SampleTable<P::ScalarField>>,
+    t_preprocess_tables: Vec<PreProcessedTable<P::G1>>,
+    f_blinders: Vec<DensePolynomial<P::ScalarField>>,
+    mut blinders: Blinders<P::ScalarField>,
+    domain: &Radix2EvaluationDomain<P::ScalarField>,
+    kzg_comm_scheme: &KZGCommitmentScheme<P>,
+) -> MultiColumnPlookUpProof<P::G1> {
+    let mut transcript = Transcript::new(b"plookup");
+    let t_comms = t_preprocess_tables
+        .iter()
+        .map(|t| t.comm)
+        .collect::<Vec<_>>();
+    for t_comm in t_comms.iter() {
+        transcript.append_commitment(t_comm);
+    }
+
+    // 1.Pad the witness with its last row and commit to every column.
+    let f_comms = f_columns
+        .iter_mut()
+        .zip(t_preprocess_tables.iter())
+        .zip(f_blinders.iter())
+        .map(|((f_column, t), f_blinder)| {
+            let last = f_column.0.last().unwrap_or(&t.table.0[0]).to_owned();
+            let eles = vec![last; domain.size() - 1 - f_column.size()];
+            f_column.0.extend(eles);
+            let f_poly = blind(
+                DensePolynomial::from_coefficients_vec(domain.ifft(&f_column.0)),
+                f_blinder,
+                domain,
+            );
+            kzg_comm_scheme.commit(&f_poly).unwrap()
+        })
+        .collect::<Vec<_>>();
+    for f_comm in f_comms.iter() {
+        transcript.append_commitment(f_comm);
+    }
+
+    // 2.Compress the columns with challenge theta, the polynomials of the table compress
+    // the same way as its columns, without another IFFT.
+    let theta = <Transcript as GlobalTranscript<P::G1>>::get_challenge(&mut transcript, b"theta");
+    let t_poly = t_preprocess_tables
+        .iter()
+        .rev()
+        .fold(DensePolynomial::zero(), |acc, t| &(&acc * theta) + &t.poly);
+    let t_columns = t_preprocess_tables
+        .into_iter()
+        .map(|t| t.table)
+        .collect::<Vec<_>>();
+    let t_table = compress_columns(&t_columns, &theta);
+    let t_preprocess_table = PreProcessedTable {
+        poly: t_poly,
+        comm: compress_commitments(&t_comms, &theta),
+        table: t_table,
+    };
+    let mut f_table = compress_columns(&f_columns, &theta);
+    let sorted_table = f_table.sort_by(&t_preprocess_table.table);
+    blinders.f = f_blinders
+        .iter()
+        .rev()
+        .fold(DensePolynomial::zero(), |acc, f_blinder| {
+            &(&acc * theta) + f_blinder
+        });
+
+    // 3.Look the compressed witness up in the compressed table.
+    let proof = prove_with_sorted_table(
+        &mut transcript,
+        f_table,
+        sorted_table,
+        t_preprocess_table,
+        &blinders,
+        domain,
+        kzg_comm_scheme,
+    );
+
+    MultiColumnPlookUpProof {
+        t_comms,
+        f_comms,
+        proof,
     }
 }
@@ -1,13 +1,24 @@
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+};
+
 use ark_ec::{pairing::Pairing, CurveGroup};
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, Zero};
 use ark_poly::{
     univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
 };
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
 use kzg::commitment::KZGCommitmentScheme;
 
-use crate::{prover, verifier::PlookUpProof};
+use crate::{
+    logup::{self, LogUpProof},
+    prover::{self, Blinders},
+    verifier::{MultiColumnPlookUpProof, PlookUpProof, TableVerifierKey},
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct SampleTable<F: PrimeField>(pub Vec<F>);
 
 impl<F: PrimeField> SampleTable<F> {
@@ -21,20 +32,25 @@ impl<F: PrimeField> SampleTable<F> {
     }
 
     pub fn pad(&mut self) {
-        let size = self.size();
-        let power_of_two = size.next_power_of_two();
-        let diff = power_of_two - size;
+        self.pad_to(self.size().next_power_of_two());
+
+        assert!(self.size().is_power_of_two())
+    }
+
+    /// Pads the table with its last element up to `size` elements.
+    pub fn pad_to(&mut self, size: usize) {
+        let diff = size.saturating_sub(self.size());
 
         let ele = vec![*self.0.last().unwrap(); diff];
         self.0.extend(ele);
-
-        assert!(self.size().is_power_of_two())
     }
 
     pub fn size(&self) -> usize {
         self.0.len()
     }
 
+    /// Commits to the table over `domain`. The prover needs the coefficients of the table
+    /// for the quotient and the openings anyway, so they are committed to directly.
     pub fn preprocess<P: Pairing<ScalarField = F>, E: EvaluationDomain<F>>(
         &self,
         kzg_comm_scheme: &KZGCommitmentScheme<P>,
@@ -42,7 +58,7 @@ impl<F: PrimeField> SampleTable<F> {
     ) -> PreProcessedTable<P::G1> {
         let coefs = domain.ifft(&self.0);
         let poly = DensePolynomial::from_coefficients_vec(coefs);
-        let comm = kzg_comm_scheme.commit(&poly);
+        let comm = kzg_comm_scheme.commit(&poly).unwrap();
 
         PreProcessedTable {
             poly,
@@ -55,79 +71,424 @@ impl<F: PrimeField> SampleTable<F> {
     /// let t = t_table,
     ///
     /// first, we should do is make sure that f is a subset of t,
-    /// second, the return table is (f, t) sorted by t.    
+    /// second, the return table is (f, t) sorted by t.
+    ///
+    /// f may repeat values, it is padded to `t_table.size() - 1` entries,
+    /// so t must be padded to fit f first.
     pub fn sort_by(&mut self, t_table: &Self) -> Self {
-        let eles = vec![self.0.last().unwrap().to_owned(); t_table.size() - 1 - self.size()];
+        assert!(
+            self.size() < t_table.size(),
+            "Look up table should be padded to more entries than prover's table"
+        );
+        let last = self.0.last().unwrap_or(&t_table.0[0]).to_owned();
+        let eles = vec![last; t_table.size() - 1 - self.size()];
         self.0.extend(eles);
 
-        let mut sorted = t_table.clone();
+        // how many times f reads each value of t.
+        let mut counts = HashMap::<F, usize>::with_capacity(t_table.size());
+        for t in t_table.0.iter() {
+            counts.entry(*t).or_insert(0);
+        }
         for x in self.0.iter() {
-            if !t_table.0.contains(x) {
-                panic!("Current prover's table is not a subset of look up table");
+            match counts.get_mut(x) {
+                Some(count) => *count += 1,
+                None => panic!("Current prover's table is not a subset of look up table"),
             }
+        }
 
-            let index = sorted.0.iter().position(|s| *x == *s).unwrap();
-            sorted.0.insert(index, *x);
+        // the reads of a value go right before its first occurrence in t.
+        let mut sorted = Vec::with_capacity(self.size() + t_table.size());
+        for t in t_table.0.iter() {
+            if let Some(count) = counts.remove(t) {
+                sorted.extend(iter::repeat_n(*t, count));
+            }
+            sorted.push(*t);
         }
 
-        sorted
+        Self(sorted)
     }
 }
 
+/// The smallest domain holding t, and f padded with at least one more entry.
+fn lookup_domain<F: PrimeField>(f_size: usize, t_size: usize) -> Radix2EvaluationDomain<F> {
+    Radix2EvaluationDomain::new((f_size + 1).max(t_size)).unwrap()
+}
+
 #[derive(Debug, Clone)]
 pub struct LookUpTable<F: PrimeField> {
     t_table: SampleTable<F>,
     f_table: Vec<F>,
-    domain: Radix2EvaluationDomain<F>,
 }
 
 impl<F: PrimeField> LookUpTable<F> {
-    pub fn new(mut t_table: SampleTable<F>) -> Self {
-        if !t_table.size().is_power_of_two() {
-            t_table.pad();
-        }
-
-        let domain = Radix2EvaluationDomain::new(t_table.size()).unwrap();
+    pub fn new(t_table: SampleTable<F>) -> Self {
+        assert!(t_table.size() > 0, "Look up table should not be empty");
 
         LookUpTable {
             t_table,
             f_table: vec![],
-            domain,
         }
     }
 
+    /// Reads `f` from the table, every read is a lookup, repeated ones included.
     pub fn read_from_u64(&mut self, f: u64) {
-        let x = F::from(f);
-        if !self.f_table.contains(&x) {
-            self.f_table.push(x);
-        }
+        self.f_table.push(F::from(f));
     }
 
     pub fn read_from_scalar(&mut self, f: &F) {
-        if !self.f_table.contains(f) {
-            self.f_table.push(f.clone());
-        }
+        self.f_table.push(*f);
     }
 
+    /// The domain of the proof, it grows with the number of reads.
+    pub fn domain(&self) -> Radix2EvaluationDomain<F> {
+        lookup_domain(self.f_table.len(), self.t_table.size())
+    }
+
+    /// Commits to the table over `self.domain()`, so the proof only verifies against the key
+    /// of a table preprocessed over the same domain, see `prove_with_table` for a stored table.
     pub fn prove<P: Pairing<ScalarField = F>>(
         &self,
         kzg_comm_scheme: &KZGCommitmentScheme<P>,
     ) -> PlookUpProof<P::G1> {
-        for f in self.f_table.iter() {
-            if !self.t_table.0.contains(f) {
-                panic!("Current prover's table is not a subset of look up table");
-            }
+        self.prove_with_blinders(kzg_comm_scheme, &Blinders::none())
+    }
+
+    /// Same as `prove`, but the witness polynomials are blinded so that the proof is
+    /// zero-knowledge. The quotient grows by 9, the SRS should support degree 2|H| + 7.
+    pub fn prove_zk<P: Pairing<ScalarField = F>, R: Rng>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        rng: &mut R,
+    ) -> PlookUpProof<P::G1> {
+        self.prove_with_blinders(kzg_comm_scheme, &Blinders::rand(rng))
+    }
+
+    /// Proves the same lookups with LogUp instead, which needs no sorting and no
+    /// f padded with one more entry, so the domain only has to hold f and t.
+    /// It is verified against the `verifier_key` of `preprocess_logup`.
+    pub fn prove_logup<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> LogUpProof<P::G1> {
+        let domain = self.logup_domain();
+        let t_preprocess_table = self.preprocess_logup(kzg_comm_scheme);
+        let f_table = SampleTable::from_scalar(self.f_table.clone());
+
+        logup::prove(f_table, t_preprocess_table, &domain, kzg_comm_scheme)
+    }
+
+    /// Same as `preprocess`, but over the domain of `prove_logup`.
+    pub fn preprocess_logup<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> PreProcessedTable<P::G1> {
+        let domain = self.logup_domain();
+        let mut t_table = self.t_table.clone();
+        t_table.pad_to(domain.size());
+        t_table.preprocess(kzg_comm_scheme, &domain)
+    }
+
+    fn logup_domain(&self) -> Radix2EvaluationDomain<F> {
+        Radix2EvaluationDomain::new(self.f_table.len().max(self.t_table.size())).unwrap()
+    }
+
+    /// Commits to the table padded to `self.domain()`, the table owner stores it and hands
+    /// its `verifier_key` to the verifier.
+    pub fn preprocess<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> PreProcessedTable<P::G1> {
+        let domain = self.domain();
+        let mut t_table = self.t_table.clone();
+        t_table.pad_to(domain.size());
+        t_table.preprocess(kzg_comm_scheme, &domain)
+    }
+
+    /// Same as `prove`, but over a stored `t_preprocess_table` instead of committing to the
+    /// table again, so the proof verifies against its key whatever the number of reads.
+    /// Its domain should hold every read with one more entry.
+    pub fn prove_with_table<P: Pairing<ScalarField = F>>(
+        &self,
+        t_preprocess_table: &PreProcessedTable<P::G1>,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> PlookUpProof<P::G1> {
+        self.prove_with_preprocessed(
+            t_preprocess_table.clone(),
+            kzg_comm_scheme,
+            &Blinders::none(),
+        )
+    }
+
+    fn prove_with_blinders<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        blinders: &Blinders<F>,
+    ) -> PlookUpProof<P::G1> {
+        let t_preprocess_table = self.preprocess(kzg_comm_scheme);
+        self.prove_with_preprocessed(t_preprocess_table, kzg_comm_scheme, blinders)
+    }
+
+    fn prove_with_preprocessed<P: Pairing<ScalarField = F>>(
+        &self,
+        t_preprocess_table: PreProcessedTable<P::G1>,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        blinders: &Blinders<F>,
+    ) -> PlookUpProof<P::G1> {
+        let t_values = t_preprocess_table.table.0.iter().collect::<HashSet<_>>();
+        if !self.f_table.iter().all(|f| t_values.contains(f)) {
+            panic!("Current prover's table is not a subset of look up table");
         }
 
-        let t_preprocess_table = self.t_table.preprocess(kzg_comm_scheme, &self.domain);
+        let domain = Radix2EvaluationDomain::new(t_preprocess_table.table.size()).unwrap();
+        assert!(
+            self.f_table.len() < domain.size(),
+            "Preprocessed table should be padded to a domain larger than the reads"
+        );
         let f_table = SampleTable::from_scalar(self.f_table.clone());
 
-        prover::prove(f_table, t_preprocess_table, &self.domain, kzg_comm_scheme)
+        prover::prove(
+            f_table,
+            t_preprocess_table,
+            blinders,
+            &domain,
+            kzg_comm_scheme,
+        )
     }
 }
 
+/// A table whose rows are tuples, such as (a, b, a XOR b).
+///
+/// The columns are compressed into a single one with a verifier challenge,
+/// which is then looked up the same way as a `LookUpTable`.
+#[derive(Debug, Clone)]
+pub struct MultiColumnTable<F: PrimeField> {
+    t_columns: Vec<SampleTable<F>>,
+    f_rows: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> MultiColumnTable<F> {
+    pub fn new(t_columns: Vec<SampleTable<F>>) -> Self {
+        assert!(!t_columns.is_empty(), "A table needs at least one column");
+        let size = t_columns[0].size();
+        assert!(size > 0, "Look up table should not be empty");
+        assert!(
+            t_columns.iter().all(|column| column.size() == size),
+            "All the columns of a table should have the same size"
+        );
+
+        MultiColumnTable {
+            t_columns,
+            f_rows: vec![],
+        }
+    }
+
+    pub fn from_rows_u64(rows: Vec<Vec<u64>>) -> Self {
+        let num_columns = rows[0].len();
+        let t_columns = (0..num_columns)
+            .map(|j| SampleTable::from_u64(rows.iter().map(|row| row[j]).collect()))
+            .collect();
+        Self::new(t_columns)
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.t_columns.len()
+    }
+
+    pub fn t_columns(&self) -> &[SampleTable<F>] {
+        &self.t_columns
+    }
+
+    pub fn read_from_u64(&mut self, row: &[u64]) {
+        let row = row.iter().map(|x| F::from(*x)).collect::<Vec<_>>();
+        self.read_from_scalar(&row);
+    }
+
+    pub fn read_from_scalar(&mut self, row: &[F]) {
+        assert_eq!(row.len(), self.num_columns());
+        self.f_rows.push(row.to_vec());
+    }
+
+    /// The domain of the proof, it grows with the number of reads.
+    pub fn domain(&self) -> Radix2EvaluationDomain<F> {
+        lookup_domain(self.f_rows.len(), self.t_columns[0].size())
+    }
+
+    fn rows(&self) -> HashSet<Vec<F>> {
+        (0..self.t_columns[0].size())
+            .map(|i| self.t_columns.iter().map(|column| column.0[i]).collect())
+            .collect()
+    }
+
+    /// Commits to every column of the table, padded to `domain`.
+    pub fn preprocess<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        domain: &Radix2EvaluationDomain<F>,
+    ) -> Vec<PreProcessedTable<P::G1>> {
+        self.t_columns
+            .iter()
+            .map(|column| {
+                let mut column = column.clone();
+                column.pad_to(domain.size());
+                column.preprocess(kzg_comm_scheme, domain)
+            })
+            .collect()
+    }
+
+    pub fn prove<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> MultiColumnPlookUpProof<P::G1> {
+        let f_blinders = vec![DensePolynomial::zero(); self.num_columns()];
+        self.prove_with_blinders(kzg_comm_scheme, f_blinders, Blinders::none())
+    }
+
+    /// Same as `prove`, but the witness polynomials are blinded so that the proof is
+    /// zero-knowledge.
+    pub fn prove_zk<P: Pairing<ScalarField = F>, R: Rng>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        rng: &mut R,
+    ) -> MultiColumnPlookUpProof<P::G1> {
+        let f_blinders = (0..self.num_columns())
+            .map(|_| DensePolynomial::rand(1, rng))
+            .collect();
+        self.prove_with_blinders(kzg_comm_scheme, f_blinders, Blinders::rand(rng))
+    }
+
+    fn prove_with_blinders<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        f_blinders: Vec<DensePolynomial<F>>,
+        blinders: Blinders<F>,
+    ) -> MultiColumnPlookUpProof<P::G1> {
+        let t_rows = self.rows();
+        if !self.f_rows.iter().all(|row| t_rows.contains(row)) {
+            panic!("Current prover's table is not a subset of look up table");
+        }
+
+        let domain = self.domain();
+        let t_preprocess_tables = self.preprocess(kzg_comm_scheme, &domain);
+        let f_columns = (0..self.num_columns())
+            .map(|j| SampleTable::from_scalar(self.f_rows.iter().map(|row| row[j]).collect()))
+            .collect();
+
+        prover::prove_multi_column(
+            f_columns,
+            t_preprocess_tables,
+            f_blinders,
+            blinders,
+            &domain,
+            kzg_comm_scheme,
+        )
+    }
+}
+
+impl<F: PrimeField> From<SampleTable<F>> for MultiColumnTable<F> {
+    fn from(t_table: SampleTable<F>) -> Self {
+        MultiColumnTable::new(vec![t_table])
+    }
+}
+
+/// Several tables looked up in a single proof, such as a range, a XOR and an S-box table.
+///
+/// Every row is prefixed with the id of its table, its position in `new`, and padded with
+/// zeros up to the widest table, so that all the lookups share one `MultiColumnTable`.
+#[derive(Debug, Clone)]
+pub struct SelectorTable<F: PrimeField> {
+    widths: Vec<usize>,
+    table: MultiColumnTable<F>,
+}
+
+impl<F: PrimeField> SelectorTable<F> {
+    pub fn new(tables: Vec<MultiColumnTable<F>>) -> Self {
+        assert!(
+            !tables.is_empty(),
+            "A selector table needs at least one table"
+        );
+        let widths = tables
+            .iter()
+            .map(|table| table.num_columns())
+            .collect::<Vec<_>>();
+        let width = *widths.iter().max().unwrap();
+
+        let mut t_columns = vec![vec![]; width + 1];
+        for (id, table) in tables.iter().enumerate() {
+            let size = table.t_columns[0].size();
+            t_columns[0].extend(vec![F::from(id as u64); size]);
+            for (j, column) in t_columns[1..].iter_mut().enumerate() {
+                match table.t_columns.get(j) {
+                    Some(t_column) => column.extend_from_slice(&t_column.0),
+                    None => column.extend(vec![F::zero(); size]),
+                }
+            }
+        }
+        let t_columns = t_columns
+            .into_iter()
+            .map(SampleTable::from_scalar)
+            .collect();
+
+        SelectorTable {
+            widths,
+            table: MultiColumnTable::new(t_columns),
+        }
+    }
+
+    pub fn num_tables(&self) -> usize {
+        self.widths.len()
+    }
+
+    pub fn read_from_u64(&mut self, table_id: usize, row: &[u64]) {
+        let row = row.iter().map(|x| F::from(*x)).collect::<Vec<_>>();
+        self.read_from_scalar(table_id, &row);
+    }
+
+    /// Reads `row` from the table `table_id`.
+    pub fn read_from_scalar(&mut self, table_id: usize, row: &[F]) {
+        assert!(table_id < self.num_tables(), "Unknown table id");
+        assert_eq!(row.len(), self.widths[table_id]);
+
+        let mut selected_row = vec![F::from(table_id as u64)];
+        selected_row.extend_from_slice(row);
+        selected_row.resize(self.table.num_columns(), F::zero());
+        self.table.read_from_scalar(&selected_row);
+    }
+
+    /// The table with the id column first, the proofs look up into.
+    pub fn table(&self) -> &MultiColumnTable<F> {
+        &self.table
+    }
+
+    pub fn prove<P: Pairing<ScalarField = F>>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+    ) -> MultiColumnPlookUpProof<P::G1> {
+        self.table.prove(kzg_comm_scheme)
+    }
+
+    pub fn prove_zk<P: Pairing<ScalarField = F>, R: Rng>(
+        &self,
+        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        rng: &mut R,
+    ) -> MultiColumnPlookUpProof<P::G1> {
+        self.table.prove_zk(kzg_comm_scheme, rng)
+    }
+}
+
+/// A table committed once, which can be stored and reused for every proof over its domain.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct PreProcessedTable<G: CurveGroup> {
     pub poly: DensePolynomial<G::ScalarField>,
     pub comm: G,
+    /// The table padded to the domain.
     pub table: SampleTable<G::ScalarField>,
 }
+
+impl<G: CurveGroup> PreProcessedTable<G> {
+    pub fn verifier_key(&self) -> TableVerifierKey<G> {
+        TableVerifierKey {
+            t_comm: self.comm,
+            domain_size: self.table.size(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Common tables, built deterministically so that the prover and the verifier
+//! commit to the same table without exchanging it.
+
+use ark_ff::PrimeField;
+
+use crate::table::{MultiColumnTable, SampleTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitwiseOp {
+    And,
+    Xor,
+    Or,
+}
+
+impl BitwiseOp {
+    pub fn apply(&self, a: u64, b: u64) -> u64 {
+        match self {
+            BitwiseOp::And => a & b,
+            BitwiseOp::Xor => a ^ b,
+            BitwiseOp::Or => a | b,
+        }
+    }
+
+    /// The row (a, b, a op b) of a `bits`-bit table packed into a single entry
+    /// a + 2^bits * b + 2^{2 * bits} * (a op b), which fits in 64 bits up to 21-bit operands.
+    pub fn pack(&self, bits: usize, a: u64, b: u64) -> u64 {
+        assert!(bits <= 21, "A packed row holds at most 21-bit operands");
+        assert!(
+            a >> bits == 0 && b >> bits == 0,
+            "Operands should have at most `bits` bits"
+        );
+        a | (b << bits) | (self.apply(a, b) << (2 * bits))
+    }
+}
+
+/// [0, 2^bits)
+pub fn range<F: PrimeField>(bits: usize) -> SampleTable<F> {
+    assert!(bits < 64, "A range table holds at most 64-bit values");
+    SampleTable::from_u64((0..1u64 << bits).collect())
+}
+
+/// (a, b, a op b) for every `bits`-bit a and b, `bitwise(BitwiseOp::Xor, 8)` is the byte XOR table.
+pub fn bitwise<F: PrimeField>(op: BitwiseOp, bits: usize) -> MultiColumnTable<F> {
+    assert!(bits <= 16, "A bitwise table holds at most 16-bit operands");
+    let rows = (0..1u64 << bits)
+        .flat_map(|a| (0..1u64 << bits).map(move |b| vec![a, b, op.apply(a, b)]))
+        .collect();
+    MultiColumnTable::from_rows_u64(rows)
+}
+
+/// Same rows as `bitwise`, packed into a single column with `BitwiseOp::pack`.
+pub fn bitwise_packed<F: PrimeField>(op: BitwiseOp, bits: usize) -> SampleTable<F> {
+    assert!(bits <= 16, "A bitwise table holds at most 16-bit operands");
+    let t = (0..1u64 << bits)
+        .flat_map(|a| (0..1u64 << bits).map(move |b| op.pack(bits, a, b)))
+        .collect();
+    SampleTable::from_u64(t)
+}
+
+/// Interleaves the bits of `x` with zeros, bit i moves to bit 2i.
+///
+/// The sum of spread values adds up the bits in place, so the even bits of
+/// spread(a) + spread(b) + spread(c) hold a XOR b XOR c and the odd ones maj(a, b, c),
+/// which is how SHA-256 circuits compute its bit operations.
+pub fn spread_bits(x: u64) -> u64 {
+    (0..32).fold(0, |acc, i| acc | (((x >> i) & 1) << (2 * i)))
+}
+
+/// (x, spread(x)) for every `bits`-bit x.
+pub fn spread<F: PrimeField>(bits: usize) -> MultiColumnTable<F> {
+    assert!(bits <= 32, "A spread table holds at most 32-bit values");
+    let rows = (0..1u64 << bits).map(|x| vec![x, spread_bits(x)]).collect();
+    MultiColumnTable::from_rows_u64(rows)
+}
+
+/// (x, sbox[x]) for every byte x.
+pub fn sbox<F: PrimeField>(sbox: &[u8; 256]) -> MultiColumnTable<F> {
+    let rows = sbox
+        .iter()
+        .enumerate()
+        .map(|(x, y)| vec![x as u64, *y as u64])
+        .collect();
+    MultiColumnTable::from_rows_u64(rows)
+}
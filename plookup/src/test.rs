@@ -1,7 +1,24 @@
-use crate::table::{LookUpTable, SampleTable};
-use ark_bls12_381::{Bls12_381, Fr};
+use crate::{
+    cq::{CqProof, CqTable, CqVerifierKey},
+    prover::{prove_with_sorted_table, Blinders},
+    table::{LookUpTable, MultiColumnTable, PreProcessedTable, SampleTable, SelectorTable},
+    tables::{self, BitwiseOp},
+    verifier::TableVerifierKey,
+};
+use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+use ark_bn254::Bn254;
+use ark_ff::{Field, One, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::test_rng;
-use kzg::{commitment::KZGCommitmentScheme, srs::SRS};
+use kzg::{
+    accumulator::PairingAccumulator, commitment::KZGCommitmentScheme, evm::encode_g1, srs::SRS,
+    transcripts::GlobalTranscript,
+};
+use merlin::Transcript;
+use std::time::Instant;
 
 #[test]
 fn test_plookup() {
@@ -17,7 +34,848 @@ fn test_plookup() {
     let mut rng = test_rng();
     let max_degree = 20;
     let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let proof = look.prove(&kzg_comm_scheme);
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(proof.verify(&vk, &table_vk));
+
+    // the domain comes from the key, which should hold a radix-2 domain of the proof's size.
+    for domain_size in [0, 9, 16] {
+        let other_vk = TableVerifierKey {
+            domain_size,
+            ..table_vk
+        };
+        assert!(!proof.verify(&vk, &other_vk));
+    }
+}
+
+#[test]
+fn test_plookup_repeated_lookups() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21]);
+    let mut look = LookUpTable::new(t);
+    // more lookups than table rows, most of them repeated.
+    for x in [
+        7, 7, 7, 1, 21, 21, 5, 7, 1, 1, 20, 21, 7, 7, 5, 5, 5, 1, 20, 7,
+    ] {
+        look.read_from_u64(x);
+    }
+    assert_eq!(look.domain().size(), 32);
+
+    let mut rng = test_rng();
+    let max_degree = 64;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let proof = look.prove(&kzg_comm_scheme);
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(proof.verify(&vk, &table_vk));
+
+    // a single read of a value out of a small table.
+    let mut look = LookUpTable::new(SampleTable::<Fr>::from_u64(vec![3]));
+    look.read_from_u64(3);
+    assert_eq!(look.domain().size(), 2);
+    let proof = look.prove(&kzg_comm_scheme);
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(proof.verify(&vk, &table_vk));
+}
+
+#[test]
+fn test_sort_by_with_duplicates() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 7, 20, 21, 24, 56]);
+    let mut f = SampleTable::<Fr>::from_u64(vec![7, 1, 7, 56]);
+    let sorted = f.sort_by(&t);
+    assert_eq!(f.size(), t.size() - 1);
+    assert_eq!(
+        sorted.0,
+        SampleTable::<Fr>::from_u64(vec![1, 1, 5, 7, 7, 7, 7, 20, 21, 24, 56, 56, 56, 56, 56]).0
+    );
+}
+
+#[test]
+fn test_sort_by_large_table() {
+    // each read inserted right before the first occurrence of its value in t.
+    let naive_sort_by = |f: &SampleTable<Fr>, t: &SampleTable<Fr>| {
+        let mut sorted = t.0.clone();
+        for x in f.0.iter() {
+            let index = sorted.iter().position(|s| s == x).unwrap();
+            sorted.insert(index, *x);
+        }
+        sorted
+    };
+
+    let table_size = 1u64 << 10;
+    // t repeats some of its values, and is not in order.
+    let t = SampleTable::<Fr>::from_u64((0..table_size).map(|i| (i * 37) % 1000).collect());
+    let mut f = SampleTable::<Fr>::from_u64((0..300).map(|i| (i * i) % 1000).collect());
+    let sorted = f.sort_by(&t);
+    assert_eq!(sorted.0, naive_sort_by(&f, &t));
+
+    let table_size = 1u64 << 16;
+    let t = SampleTable::<Fr>::from_u64((0..table_size).collect());
+    let mut f =
+        SampleTable::<Fr>::from_u64((0..table_size - 1).map(|i| (i * i) % table_size).collect());
+    let sorted = f.sort_by(&t);
+    assert_eq!(sorted.size(), 2 * table_size as usize - 1);
+}
+
+#[test]
+fn test_zk_plookup() {
+    let mut rng = test_rng();
+    let max_degree = 24;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let mut prove = |reads: &[u64], zk: bool| {
+        let mut look = LookUpTable::new(t.clone());
+        for x in reads {
+            look.read_from_u64(*x);
+        }
+        let proof = if zk {
+            look.prove_zk(&kzg_comm_scheme, &mut rng)
+        } else {
+            look.prove(&kzg_comm_scheme)
+        };
+        let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+        assert!(proof.verify(&vk, &table_vk));
+        proof.evaluations
+    };
+
+    // without blinding, the openings are a function of the witness.
+    assert_eq!(prove(&[24, 21, 56], false), prove(&[24, 21, 56], false));
+
+    // with blinding, the openings of f, h1, h2 and z are fresh random values, whatever the
+    // witness is, so two proofs of the same witness share none of them.
+    let evals = [
+        prove(&[24, 21, 56], true),
+        prove(&[24, 21, 56], true),
+        prove(&[1, 1, 100], true),
+    ];
+    for (i, a) in evals.iter().enumerate() {
+        for b in evals[i + 1..].iter() {
+            assert_ne!(a.f_eval_zeta, b.f_eval_zeta);
+            assert_ne!(a.h1_eval_zeta, b.h1_eval_zeta);
+            assert_ne!(a.h1_eval_zeta_omega, b.h1_eval_zeta_omega);
+            assert_ne!(a.h2_eval_zeta_omega, b.h2_eval_zeta_omega);
+            assert_ne!(a.z_eval_zeta_omega, b.z_eval_zeta_omega);
+        }
+    }
+}
+
+#[test]
+fn test_accumulate_plookup() {
+    let mut rng = test_rng();
+    let max_degree = 20;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+    let mut accumulator = PairingAccumulator::new();
+    for reads in [vec![1, 5, 5], vec![100, 24, 7, 7, 1]] {
+        let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+        let mut look = LookUpTable::new(t);
+        for x in reads {
+            look.read_from_u64(x);
+        }
+        let proof = look.prove(&kzg_comm_scheme);
+        let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+        assert!(proof.accumulate(&vk, &table_vk, &mut accumulator, &mut rng));
+    }
+    assert!(accumulator.check(&vk).is_ok());
+}
+
+#[test]
+fn test_plookup_bn254() {
+    let t = SampleTable::<ark_bn254::Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let mut look = LookUpTable::new(t);
+    look.read_from_u64(7);
+    look.read_from_u64(100);
+
+    let mut rng = test_rng();
+    let max_degree = 20;
+    let srs = SRS::<Bn254>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let proof = look.prove(&kzg_comm_scheme);
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(proof.verify(&vk, &table_vk));
+
+    let bytes = proof.to_evm_bytes();
+    assert_eq!(bytes.len(), 6 * 64 + 7 * 32 + 2 * 64);
+    assert_eq!(table_vk.domain_size, 8);
+    assert_eq!(bytes[..64], encode_g1(&proof.f_comm));
+}
+
+#[test]
+fn test_plookup_rejects_tampered_proof() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let mut look = LookUpTable::new(t);
+    look.read_from_u64(20);
+    look.read_from_u64(1);
+
+    let mut rng = test_rng();
+    let max_degree = 20;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let mut proof = look.prove(&kzg_comm_scheme);
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(proof.verify(&vk, &table_vk));
+
+    proof.evaluations.h1_eval_zeta += Fr::one();
+    assert!(!proof.verify(&vk, &table_vk));
+    proof.evaluations.h1_eval_zeta -= Fr::one();
+
+    proof.q_comm += vk.g1;
+    assert!(!proof.verify(&vk, &table_vk));
+}
+
+#[test]
+fn test_plookup_rejects_out_of_table_witness() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    // 8 is not in t, sort it as if it was right after 7.
+    let f = SampleTable::<Fr>::from_u64(vec![5, 8, 8, 8, 8, 8, 8]);
+    let sorted =
+        SampleTable::<Fr>::from_u64(vec![1, 5, 5, 7, 8, 8, 8, 8, 8, 8, 20, 21, 24, 56, 100]);
+
+    let mut rng = test_rng();
+    let max_degree = 20;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let domain = Radix2EvaluationDomain::new(t.size()).unwrap();
+    let t_preprocess_table = t.preprocess(&kzg_comm_scheme, &domain);
+    let table_vk = t_preprocess_table.verifier_key();
+
+    let proof = prove_with_sorted_table(
+        &mut Transcript::new(b"plookup"),
+        f,
+        sorted,
+        t_preprocess_table,
+        &Blinders::none(),
+        &domain,
+        &kzg_comm_scheme,
+    );
+    assert!(!proof.verify(&vk, &table_vk));
+
+    let mut accumulator = PairingAccumulator::new();
+    proof.accumulate(&vk, &table_vk, &mut accumulator, &mut rng);
+    assert!(accumulator.check(&vk).is_err());
+}
+
+#[test]
+fn test_multi_column_plookup() {
+    // (a, b, a XOR b) for 2-bit a and b.
+    let rows = (0..4u64)
+        .flat_map(|a| (0..4u64).map(move |b| vec![a, b, a ^ b]))
+        .collect::<Vec<_>>();
+    let mut look = MultiColumnTable::<Fr>::from_rows_u64(rows);
+    look.read_from_u64(&[1, 2, 3]);
+    look.read_from_u64(&[3, 3, 0]);
+    look.read_from_u64(&[2, 3, 1]);
+    look.read_from_u64(&[1, 2, 3]);
+
+    let mut rng = test_rng();
+    let max_degree = 40;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let mut proof = look.prove(&kzg_comm_scheme);
+    let table_vks = look
+        .preprocess(&kzg_comm_scheme, &look.domain())
+        .iter()
+        .map(|t| t.verifier_key())
+        .collect::<Vec<_>>();
+    assert!(proof.verify(&vk, &table_vks));
+
+    // every column should be pinned over the same domain.
+    let mut other_vks = table_vks.clone();
+    other_vks[1].domain_size *= 2;
+    assert!(!proof.verify(&vk, &other_vks));
+
+    let mut accumulator = PairingAccumulator::new();
+    assert!(proof.accumulate(&vk, &table_vks, &mut accumulator, &mut rng));
+    assert!(accumulator.check(&vk).is_ok());
+    assert!(look
+        .prove_zk(&kzg_comm_scheme, &mut rng)
+        .verify(&vk, &table_vks));
+
+    // the columns of the witness are bound to the compressed commitment.
+    proof.f_comms.swap(0, 1);
+    assert!(!proof.verify(&vk, &table_vks));
+    proof.f_comms.swap(0, 1);
+
+    proof.t_comms.pop();
+    assert!(!proof.verify(&vk, &table_vks));
+}
+
+#[test]
+#[should_panic(expected = "not a subset")]
+fn test_multi_column_plookup_rejects_missing_row() {
+    let rows = vec![vec![0, 0, 0], vec![0, 1, 1], vec![1, 0, 1], vec![1, 1, 0]];
+    let mut look = MultiColumnTable::<Fr>::from_rows_u64(rows);
+    // every entry is in its column, but the row is not in the table.
+    look.read_from_u64(&[1, 1, 1]);
+
+    let mut rng = test_rng();
+    let srs = SRS::<Bls12_381>::new(16, &mut rng);
+    let (pk, _) = srs.trim(16).unwrap();
+    look.prove(&KZGCommitmentScheme(&pk));
+}
+
+#[test]
+fn test_selector_table() {
+    let mut sbox = [0u8; 256];
+    for (x, y) in sbox.iter_mut().enumerate() {
+        *y = (x as u8).wrapping_mul(167).wrapping_add(13);
+    }
+    let mut look = SelectorTable::<Fr>::new(vec![
+        tables::range(6).into(),
+        tables::bitwise(BitwiseOp::Xor, 3),
+        tables::sbox(&sbox),
+    ]);
+    assert_eq!(look.table().num_columns(), 4);
+    look.read_from_u64(0, &[42]);
+    look.read_from_u64(1, &[5, 6, 3]);
+    look.read_from_u64(2, &[7, sbox[7] as u64]);
+    look.read_from_u64(0, &[0]);
+    look.read_from_u64(1, &[7, 7, 0]);
+
+    let mut rng = test_rng();
+    let max_degree = 2 * 512 + 8;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let table_vks = look
+        .table()
+        .preprocess(&kzg_comm_scheme, &look.table().domain())
+        .iter()
+        .map(|t| t.verifier_key())
+        .collect::<Vec<_>>();
+
+    // a single plookup instance for all three tables.
+    let proof = look.prove(&kzg_comm_scheme);
+    assert!(table_vks.iter().all(|table_vk| table_vk.domain_size == 512));
+    assert!(proof.verify(&vk, &table_vks));
+    assert!(look
+        .prove_zk(&kzg_comm_scheme, &mut rng)
+        .verify(&vk, &table_vks));
+}
+
+#[test]
+#[should_panic(expected = "not a subset")]
+fn test_selector_table_rejects_row_of_another_table() {
+    let mut look = SelectorTable::<Fr>::new(vec![
+        tables::range(2).into(),
+        MultiColumnTable::from_rows_u64(vec![vec![5, 0], vec![6, 1]]),
+    ]);
+    // (5, 0) is a row of the second table, 5 is not in the range table.
+    look.read_from_u64(0, &[5]);
+    look.read_from_u64(1, &[5, 0]);
+
+    let mut rng = test_rng();
+    let srs = SRS::<Bls12_381>::new(24, &mut rng);
+    let (pk, _) = srs.trim(24).unwrap();
+    look.prove(&KZGCommitmentScheme(&pk));
+}
+
+#[test]
+fn test_logup() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let mut look = LookUpTable::new(t);
+    for x in [24, 21, 56, 56, 56, 1, 100, 100, 7, 7, 7] {
+        look.read_from_u64(x);
+    }
+
+    let mut rng = test_rng();
+    let max_degree = 64;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let table_vk = look.preprocess_logup(&kzg_comm_scheme).verifier_key();
+    let mut proof = look.prove_logup(&kzg_comm_scheme);
+    assert_eq!(table_vk.domain_size, 16);
+    assert!(proof.verify(&vk, &table_vk));
+
+    // the domain comes from the key, which should hold a radix-2 domain of the proof's size.
+    for domain_size in [17, 32] {
+        let other_vk = TableVerifierKey {
+            domain_size,
+            ..table_vk
+        };
+        assert!(!proof.verify(&vk, &other_vk));
+    }
+
+    let mut accumulator = PairingAccumulator::new();
+    assert!(proof.accumulate(&vk, &table_vk, &mut accumulator, &mut rng));
+    assert!(accumulator.check(&vk).is_ok());
+
+    proof.s_eval_zeta_omega += Fr::one();
+    assert!(!proof.verify(&vk, &table_vk));
+    proof.s_eval_zeta_omega -= Fr::one();
+
+    proof.m_comm += vk.g1;
+    assert!(!proof.verify(&vk, &table_vk));
+}
+
+#[test]
+fn test_logup_rejects_another_table() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let look = LookUpTable::new(t);
+
+    let mut rng = test_rng();
+    let max_degree = 32;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let table_vk = look.preprocess_logup(&kzg_comm_scheme).verifier_key();
+
+    // 99 is not in the table of the verifier, the proof is valid for the prover's own table.
+    let mut other = LookUpTable::new(SampleTable::<Fr>::from_u64(vec![
+        1, 5, 7, 20, 21, 24, 56, 99,
+    ]));
+    other.read_from_u64(99);
+    let proof = other.prove_logup(&kzg_comm_scheme);
+    let other_vk = other.preprocess_logup(&kzg_comm_scheme).verifier_key();
+    assert!(proof.verify(&vk, &other_vk));
+    assert!(!proof.verify(&vk, &table_vk));
+
+    let mut accumulator = PairingAccumulator::new();
+    assert!(!proof.accumulate(&vk, &table_vk, &mut accumulator, &mut rng));
+}
+
+#[test]
+#[should_panic(expected = "not a subset")]
+fn test_logup_rejects_out_of_table_witness() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20]);
+    let mut look = LookUpTable::new(t);
+    look.read_from_u64(8);
+
+    let mut rng = test_rng();
+    let srs = SRS::<Bls12_381>::new(16, &mut rng);
+    let (pk, _) = srs.trim(16).unwrap();
+    look.prove_logup(&KZGCommitmentScheme(&pk));
+}
+
+#[test]
+fn test_logup_against_plookup() {
+    let table_size = 1 << 8;
+    let t = SampleTable::<ark_bn254::Fr>::from_u64((0..table_size).collect());
+    let mut look = LookUpTable::new(t);
+    for i in 0..table_size - 1 {
+        look.read_from_u64((i * i + 7) % table_size);
+    }
+
+    let mut rng = test_rng();
+    let max_degree = 2 * table_size as usize + 8;
+    let srs = SRS::<Bn254>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+    let now = Instant::now();
+    let plookup_proof = look.prove(&kzg_comm_scheme);
+    println!(
+        "plookup prover of {} lookups: {:?}",
+        table_size - 1,
+        now.elapsed()
+    );
+    let now = Instant::now();
+    let logup_proof = look.prove_logup(&kzg_comm_scheme);
+    println!(
+        "logup prover of {} lookups: {:?}",
+        table_size - 1,
+        now.elapsed()
+    );
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(plookup_proof.verify(&vk, &table_vk));
+    let table_vk = look.preprocess_logup(&kzg_comm_scheme).verifier_key();
+    assert!(logup_proof.verify(&vk, &table_vk));
+
+    let plookup_size = plookup_proof.to_evm_bytes().len();
+    let logup_size = logup_proof.to_evm_bytes().len();
+    println!(
+        "plookup proof: {} bytes, logup proof: {} bytes",
+        plookup_size, logup_size
+    );
+    assert_eq!(logup_size, 5 * 64 + 3 * 32 + 2 * 64);
+    assert!(logup_size < plookup_size);
+}
+
+#[test]
+fn test_cq() {
+    let mut rng = test_rng();
+    let table_size = 1 << 8;
+    let srs = SRS::<Bls12_381>::new_with_g2_powers(table_size, table_size, &mut rng);
+    let kzg_comm_scheme = KZGCommitmentScheme(&srs);
+    let t = SampleTable::<Fr>::from_u64((0..table_size as u64).collect());
+    let cq_table = CqTable::new(t, &srs);
+
+    let f = SampleTable::<Fr>::from_u64(vec![3, 255, 0, 3, 3, 128, 77]);
+    let mut proof = cq_table.prove(&f, &kzg_comm_scheme);
+    assert_eq!(proof.size, 8);
+    assert!(proof.verify(cq_table.verifier_key()));
+
+    proof.a_eval_zero += Fr::one();
+    assert!(!proof.verify(cq_table.verifier_key()));
+    proof.a_eval_zero -= Fr::one();
+    proof.f_eval_gamma += Fr::one();
+    assert!(!proof.verify(cq_table.verifier_key()));
+    proof.f_eval_gamma -= Fr::one();
+    proof.q_a_comm += srs.g1[0];
+    assert!(!proof.verify(cq_table.verifier_key()));
+    proof.q_a_comm -= srs.g1[0];
+    assert!(proof.verify(cq_table.verifier_key()));
+}
+
+#[test]
+#[should_panic(expected = "not a subset")]
+fn test_cq_rejects_out_of_table_witness() {
+    let mut rng = test_rng();
+    let srs = SRS::<Bls12_381>::new_with_g2_powers(8, 8, &mut rng);
+    let kzg_comm_scheme = KZGCommitmentScheme(&srs);
+    let cq_table = CqTable::new(SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20]), &srs);
+    cq_table.prove(&SampleTable::from_u64(vec![5, 6]), &kzg_comm_scheme);
+}
+
+/// Builds a cq proof from the multiplicities `m` over the table, like the prover, but commits to
+/// A(X) + c * Z_V(X), Q_A(X) + c * (T(X) + β) and A_0(X) + c * X^{N - 1} with c such that
+/// N * A(0) = \sum 1 / (β + f_i), which holds for any f. c is zero for an honest witness.
+fn forge_cq_proof(
+    srs: &SRS<Bls12_381>,
+    vk: &CqVerifierKey<Bls12_381>,
+    t: &[u64],
+    f: &[u64],
+    m: &[u64],
+) -> CqProof<G1Projective> {
+    let kzg_comm_scheme = KZGCommitmentScheme(srs);
+    let max_degree = srs.g1.len() - 1;
+    let domain_v = vk.domain;
+    let domain_k = Radix2EvaluationDomain::<Fr>::new(f.len()).unwrap();
+    let interpolate = |values: &[u64], domain: &Radix2EvaluationDomain<Fr>| {
+        let values = values.iter().map(|x| Fr::from(*x)).collect::<Vec<_>>();
+        DensePolynomial::from_coefficients_vec(domain.ifft(&values))
+    };
+    let shift = |poly: &DensePolynomial<Fr>, shift: usize| {
+        let mut coeffs = vec![Fr::zero(); shift];
+        coeffs.extend_from_slice(poly.coeffs());
+        coeffs.truncate(max_degree + 1);
+        DensePolynomial::from_coefficients_vec(coeffs)
+    };
+
+    let t_poly = interpolate(t, &domain_v);
+    let f_poly = interpolate(f, &domain_k);
+    let m_poly = interpolate(m, &domain_v);
+    let f_comm = kzg_comm_scheme.commit(&f_poly).unwrap();
+    let m_comm = kzg_comm_scheme.commit(&m_poly).unwrap();
+
+    let mut transcript = Transcript::new(b"cq");
+    transcript.append_commitment(&vk.t_comm);
+    transcript.append_u64(b"size", f.len() as u64);
+    transcript.append_commitment(&f_comm);
+    transcript.append_commitment(&m_comm);
+    let beta =
+        <Transcript as GlobalTranscript<G1Projective>>::get_challenge(&mut transcript, b"beta");
+
+    let a_values = t
+        .iter()
+        .zip(m.iter())
+        .map(|(t, m)| Fr::from(*m) / (beta + Fr::from(*t)))
+        .collect::<Vec<_>>();
+    let b_values = f
+        .iter()
+        .map(|f| (beta + Fr::from(*f)).inverse().unwrap())
+        .collect::<Vec<_>>();
+    let a_poly = DensePolynomial::from_coefficients_vec(domain_v.ifft(&a_values));
+    let b_poly = DensePolynomial::from_coefficients_vec(domain_k.ifft(&b_values));
+
+    // Z_V(0) = -1
+    let c = a_poly.coeffs()[0] - b_values.iter().sum::<Fr>() * domain_v.size_inv;
+    let z_v_poly: DensePolynomial<Fr> = domain_v.vanishing_polynomial().into();
+    let a_poly = &a_poly + &(&z_v_poly * c);
+
+    let beta_poly = DensePolynomial::from_coefficients_vec(vec![beta]);
+    let (q_a_poly, remainder) = (&(&a_poly * &(&t_poly + &beta_poly)) - &m_poly)
+        .divide_by_vanishing_poly(domain_v)
+        .unwrap();
+    assert!(remainder.is_zero());
+    let a_zero_poly = DensePolynomial::from_coefficients_slice(&a_poly.coeffs()[1..]);
+    let b_zero_poly = DensePolynomial::from_coefficients_slice(&b_poly.coeffs()[1..]);
+    let one = DensePolynomial::from_coefficients_vec(vec![Fr::one()]);
+    let (q_b_poly, remainder) = (&(&b_poly * &(&f_poly + &beta_poly)) - &one)
+        .divide_by_vanishing_poly(domain_k)
+        .unwrap();
+    assert!(remainder.is_zero());
+
+    // P_A(X) = A(X) * X^{D - N + 1} is cut at degree D, the SRS has no higher power of τ.
+    let p_a_poly = shift(&a_poly, max_degree - domain_v.size() + 1);
+    let p_poly = shift(&b_zero_poly, max_degree - domain_k.size() + 2);
+
+    let comms = [
+        &a_poly,
+        &q_a_poly,
+        &a_zero_poly,
+        &b_zero_poly,
+        &q_b_poly,
+        &p_poly,
+        &p_a_poly,
+    ]
+    .map(|poly| kzg_comm_scheme.commit(poly).unwrap());
+    for comm in comms.iter() {
+        transcript.append_commitment(comm);
+    }
+
+    let gamma =
+        <Transcript as GlobalTranscript<G1Projective>>::get_challenge(&mut transcript, b"gamma");
+    let (opening_witness, evaluations) = kzg_comm_scheme
+        .multi_open(
+            &[b_zero_poly, f_poly, q_b_poly],
+            &[comms[3], f_comm, comms[4]],
+            &[vec![gamma], vec![gamma], vec![gamma]],
+            &mut transcript,
+        )
+        .unwrap();
+
+    CqProof {
+        f_comm,
+        m_comm,
+        a_comm: comms[0],
+        q_a_comm: comms[1],
+        a_zero_comm: comms[2],
+        b_zero_comm: comms[3],
+        q_b_comm: comms[4],
+        p_comm: comms[5],
+        p_a_comm: comms[6],
+        a_eval_zero: a_poly.coeffs()[0],
+        b_zero_eval_gamma: evaluations[0][0],
+        f_eval_gamma: evaluations[1][0],
+        opening_witness,
+        size: domain_k.size(),
+    }
+}
+
+#[test]
+fn test_cq_rejects_high_degree_a() {
+    let mut rng = test_rng();
+    let srs = SRS::<Bls12_381>::new_with_g2_powers(8, 8, &mut rng);
+    let t = (0..8).collect::<Vec<u64>>();
+    let cq_table = CqTable::new(SampleTable::<Fr>::from_u64(t.clone()), &srs);
+    let vk = cq_table.verifier_key();
+
+    let proof = forge_cq_proof(&srs, vk, &t, &[3, 5, 5, 0], &[1, 0, 0, 1, 0, 2, 0, 0]);
+    assert!(proof.verify(vk));
+
+    // 1000 is not in the table, A(X) + c * Z_V(X) has degree N.
+    let proof = forge_cq_proof(&srs, vk, &t, &[3, 5, 5, 1000], &[0, 0, 0, 1, 0, 2, 0, 0]);
+    assert!(!proof.verify(vk));
+}
+
+#[test]
+fn test_cq_rejects_another_domain_size() {
+    let mut rng = test_rng();
+    let srs = SRS::<Bls12_381>::new_with_g2_powers(16, 16, &mut rng);
+    let t = (0..16).collect::<Vec<u64>>();
+    let cq_table = CqTable::new(SampleTable::<Fr>::from_u64(t.clone()), &srs);
+    let vk = cq_table.verifier_key();
+
+    let mut proof = forge_cq_proof(&srs, vk, &t, &[3, 5, 5, 1], &[0, 1, 0, 1, 0, 2, 0, 0]);
+    assert!(proof.verify(vk));
+
+    // sizes that are not a radix-2 domain, or one that does not fit the table, are rejected
+    // without panicking, and the domain of another size does not verify.
+    for size in [0, 1, 2, 3, 8, 32, usize::MAX] {
+        proof.size = size;
+        assert!(!proof.verify(vk));
+    }
+}
+
+#[test]
+fn test_cq_prover_against_table_size() {
+    let mut rng = test_rng();
+    let max_table_size = 1 << 9;
+    let srs = SRS::<Bn254>::new_with_g2_powers(max_table_size, max_table_size, &mut rng);
     let kzg_comm_scheme = KZGCommitmentScheme(&srs);
+    let f = SampleTable::<ark_bn254::Fr>::from_u64((0..16).map(|i| (i * i + 7) % 64).collect());
+
+    for table_size in [1 << 6, max_table_size] {
+        let t = SampleTable::<ark_bn254::Fr>::from_u64((0..table_size as u64).collect());
+        let now = Instant::now();
+        let cq_table = CqTable::new(t, &srs);
+        println!(
+            "cq preprocessing of a table of {}: {:?}",
+            table_size,
+            now.elapsed()
+        );
+
+        let now = Instant::now();
+        let proof = cq_table.prove(&f, &kzg_comm_scheme);
+        println!(
+            "cq prover of {} lookups in a table of {}: {:?}",
+            f.size(),
+            table_size,
+            now.elapsed()
+        );
+        assert!(proof.verify(cq_table.verifier_key()));
+    }
+}
+
+/// Proves `member` is in `t` against the key of `t`, then proves `non_member` with a table where
+/// it replaces the last entry, which the key of `t` rejects.
+fn check_standard_table(t: SampleTable<Fr>, member: u64, non_member: u64) {
+    let mut rng = test_rng();
+    let max_degree = 2 * t.size() + 8;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+    let mut look = LookUpTable::new(t.clone());
+    look.read_from_u64(member);
+    let table_vk = look.preprocess(&kzg_comm_scheme).verifier_key();
+    assert!(look.prove(&kzg_comm_scheme).verify(&vk, &table_vk));
+
+    let mut forged = t;
+    *forged.0.last_mut().unwrap() = Fr::from(non_member);
+    let mut look = LookUpTable::new(forged);
+    look.read_from_u64(non_member);
     let proof = look.prove(&kzg_comm_scheme);
-    assert!(proof.verify(&kzg_comm_scheme, &mut rng));
+    assert!(proof.verify(&vk, &look.preprocess(&kzg_comm_scheme).verifier_key()));
+    assert!(!proof.verify(&vk, &table_vk));
+}
+
+/// Same as `check_standard_table` for the rows of a multi-column table.
+fn check_standard_multi_column_table(t: MultiColumnTable<Fr>, member: &[u64], non_member: &[u64]) {
+    let mut rng = test_rng();
+    let max_degree = 2 * t.t_columns()[0].size() + 8;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+    let verifier_keys = |look: &MultiColumnTable<Fr>| {
+        look.preprocess(&kzg_comm_scheme, &look.domain())
+            .iter()
+            .map(|column| column.verifier_key())
+            .collect::<Vec<_>>()
+    };
+
+    let mut look = t.clone();
+    look.read_from_u64(member);
+    let table_vks = verifier_keys(&look);
+    assert!(look.prove(&kzg_comm_scheme).verify(&vk, &table_vks));
+
+    let mut forged_columns = t.t_columns().to_vec();
+    for (column, x) in forged_columns.iter_mut().zip(non_member.iter()) {
+        *column.0.last_mut().unwrap() = Fr::from(*x);
+    }
+    let mut look = MultiColumnTable::new(forged_columns);
+    look.read_from_u64(non_member);
+    let proof = look.prove(&kzg_comm_scheme);
+    assert!(proof.verify(&vk, &verifier_keys(&look)));
+    assert!(!proof.verify(&vk, &table_vks));
+}
+
+#[test]
+fn test_range_table() {
+    check_standard_table(tables::range(8), 200, 256);
+}
+
+#[test]
+fn test_bitwise_tables() {
+    for op in [BitwiseOp::And, BitwiseOp::Xor, BitwiseOp::Or] {
+        let t = tables::bitwise(op, 4);
+        assert_eq!(t.t_columns()[0].size(), 256);
+        check_standard_multi_column_table(t, &[9, 12, op.apply(9, 12)], &[9, 12, 16]);
+
+        check_standard_table(
+            tables::bitwise_packed(op, 4),
+            op.pack(4, 9, 12),
+            // a wrong lowest bit of the result.
+            op.pack(4, 9, 12) ^ (1 << 8),
+        );
+    }
+
+    // the widest operands that still fit in 64 bits.
+    let max = (1 << 21) - 1;
+    assert_eq!(BitwiseOp::Or.pack(21, max, max), u64::MAX >> 1);
+}
+
+#[test]
+#[should_panic(expected = "at most 21-bit operands")]
+fn test_bitwise_pack_rejects_wide_operands() {
+    BitwiseOp::Xor.pack(22, 1, 1);
+}
+
+#[test]
+fn test_spread_table() {
+    assert_eq!(tables::spread_bits(0b1011), 0b1000101);
+    let (a, b, c) = (0b1100u64, 0b1010, 0b0110);
+    let sum = tables::spread_bits(a) + tables::spread_bits(b) + tables::spread_bits(c);
+    let even = (0..4).fold(0, |acc, i| acc | (((sum >> (2 * i)) & 1) << i));
+    let odd = (0..4).fold(0, |acc, i| acc | (((sum >> (2 * i + 1)) & 1) << i));
+    assert_eq!(even, a ^ b ^ c);
+    assert_eq!(odd, (a & b) | (a & c) | (b & c));
+
+    check_standard_multi_column_table(
+        tables::spread(8),
+        &[0b10110011, tables::spread_bits(0b10110011)],
+        &[0b10110011, 0b10110011],
+    );
+}
+
+#[test]
+fn test_sbox_table() {
+    // an affine permutation of the bytes.
+    let mut sbox = [0u8; 256];
+    for (x, y) in sbox.iter_mut().enumerate() {
+        *y = (x as u8).wrapping_mul(167).wrapping_add(13);
+    }
+    let t = tables::sbox(&sbox);
+    check_standard_multi_column_table(t, &[42, sbox[42] as u64], &[42, sbox[43] as u64]);
+}
+
+#[test]
+fn test_stored_table() {
+    let t = SampleTable::<Fr>::from_u64(vec![1, 5, 7, 20, 21, 24, 56, 100]);
+    let mut look = LookUpTable::new(t);
+    look.read_from_u64(21);
+    look.read_from_u64(100);
+
+    let mut rng = test_rng();
+    let max_degree = 40;
+    let srs = SRS::<Bls12_381>::new(max_degree, &mut rng);
+    let (pk, vk) = srs.trim(max_degree).unwrap();
+    let kzg_comm_scheme = KZGCommitmentScheme(&pk);
+
+    // the table owner stores the table, and hands its key to the verifier.
+    let mut bytes = vec![];
+    look.preprocess(&kzg_comm_scheme)
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    let mut key_bytes = vec![];
+    look.preprocess(&kzg_comm_scheme)
+        .verifier_key()
+        .serialize_compressed(&mut key_bytes)
+        .unwrap();
+
+    let t_preprocess_table = PreProcessedTable::deserialize_compressed(&bytes[..]).unwrap();
+    let table_vk = TableVerifierKey::deserialize_compressed(&key_bytes[..]).unwrap();
+    let proof = look.prove_with_table(&t_preprocess_table, &kzg_comm_scheme);
+    assert!(proof.verify(&vk, &table_vk));
+
+    // a proof over another table, or over another domain, is rejected.
+    let mut other = LookUpTable::new(SampleTable::<Fr>::from_u64(vec![
+        1, 5, 7, 20, 21, 24, 56, 99,
+    ]));
+    other.read_from_u64(21);
+    assert!(!other.prove(&kzg_comm_scheme).verify(&vk, &table_vk));
+
+    // the stored table holds up to 7 reads, and the proofs against it keep its domain.
+    for _ in 0..5 {
+        look.read_from_u64(1);
+    }
+    let proof = look.prove_with_table(&t_preprocess_table, &kzg_comm_scheme);
+    assert!(proof.verify(&vk, &table_vk));
+    for _ in 0..3 {
+        look.read_from_u64(1);
+    }
+    assert!(!look.prove(&kzg_comm_scheme).verify(&vk, &table_vk));
 }
@@ -1,57 +1,298 @@
 use std::ops::Mul;
 
 use ark_ec::{pairing::Pairing, CurveGroup};
-use ark_poly::Radix2EvaluationDomain;
+use ark_ff::{PrimeField, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::Rng;
-use kzg::commitment::{KZGCommitmentProof, KZGCommitmentScheme};
+use kzg::{
+    accumulator::PairingAccumulator, commitment::KZGMultiOpeningProof, srs::VerifierKey,
+    transcripts::GlobalTranscript,
+};
 use merlin::Transcript;
 
-use crate::transcripts::GlobalTranscript;
+use crate::helpers::{compress_commitments, compute_linearization_coefficients};
+
+/// Evaluations the verifier needs to rebuild the linearization r(X).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlookUpEvaluations<F: PrimeField> {
+    pub f_eval_zeta: F,
+    pub t_eval_zeta: F,
+    pub t_eval_zeta_omega: F,
+    pub h1_eval_zeta: F,
+    pub h1_eval_zeta_omega: F,
+    pub h2_eval_zeta_omega: F,
+    pub z_eval_zeta_omega: F,
+}
+
+/// Pins the table a proof looks up into, so that the prover cannot substitute its own.
+///
+/// The verifier rebuilds the domain from `domain_size`, and the domain of `LookUpTable::prove`
+/// grows with the reads, so proofs checked against a stored key should come from
+/// `LookUpTable::prove_with_table` over the stored table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TableVerifierKey<G: CurveGroup> {
+    /// Commitment to the table padded to the domain.
+    pub t_comm: G,
+    pub domain_size: usize,
+}
+
+impl<G: CurveGroup> TableVerifierKey<G> {
+    /// The domain of the proofs over the table, rebuilt from the key rather than taken from
+    /// a proof. `None` if `domain_size` is not the size of a radix-2 domain.
+    pub(crate) fn domain(&self) -> Option<Radix2EvaluationDomain<G::ScalarField>> {
+        Radix2EvaluationDomain::new(self.domain_size)
+            .filter(|domain| domain.size() == self.domain_size)
+    }
+
+    pub(crate) fn pins(&self, t_comm: &G) -> bool {
+        self.t_comm == *t_comm
+    }
+}
 
 pub struct PlookUpProof<G: CurveGroup> {
     pub f_comm: G,
     pub t_comm: G,
     pub h1_comm: G,
     pub h2_comm: G,
-    pub r_comm: G,
     pub z_comm: G,
-    pub opening_witness_zeta: KZGCommitmentProof<G>,
-    pub opening_witness_zeta_omega: KZGCommitmentProof<G>,
-    pub domain: Radix2EvaluationDomain<G::ScalarField>,
+    pub q_comm: G,
+    pub evaluations: PlookUpEvaluations<G::ScalarField>,
+    pub opening_witness: KZGMultiOpeningProof<G>,
 }
 
 impl<G: CurveGroup> PlookUpProof<G> {
-    pub fn verify<P: Pairing<G1 = G, ScalarField = G::ScalarField>, R: Rng>(
+    /// The domain is rebuilt from `table_vk`, the proof does not carry it.
+    pub fn verify<P: Pairing<G1 = G, ScalarField = G::ScalarField>>(
+        &self,
+        vk: &VerifierKey<P>,
+        table_vk: &TableVerifierKey<G>,
+    ) -> bool {
+        match table_vk.domain() {
+            Some(domain) if table_vk.pins(&self.t_comm) => {
+                self.verify_with_transcript(vk, Transcript::new(b"plookup"), &domain)
+            }
+            _ => false,
+        }
+    }
+
+    /// Same as `verify`, but resumes from `transcript` over `domain`.
+    pub(crate) fn verify_with_transcript<P: Pairing<G1 = G, ScalarField = G::ScalarField>>(
         &self,
-        kzg_comm_scheme: &KZGCommitmentScheme<P>,
+        vk: &VerifierKey<P>,
+        transcript: Transcript,
+        domain: &Radix2EvaluationDomain<G::ScalarField>,
+    ) -> bool {
+        let (mut transcript, comms, points, evaluations) =
+            self.replay_transcript(&vk.g1, transcript, domain);
+        vk.multi_verify(
+            &comms,
+            &points,
+            &evaluations,
+            &self.opening_witness,
+            &mut transcript,
+        )
+        .is_ok()
+    }
+
+    /// Same as `verify`, but the pairing check is deferred to `accumulator`,
+    /// which checks all the accumulated proofs at once.
+    pub fn accumulate<P: Pairing<G1 = G, ScalarField = G::ScalarField>, R: Rng>(
+        &self,
+        vk: &VerifierKey<P>,
+        table_vk: &TableVerifierKey<G>,
+        accumulator: &mut PairingAccumulator<G>,
         rng: &mut R,
     ) -> bool {
-        let mut transcript = Transcript::new(b"plookup");
-        transcript.append_u64(b"size", self.domain.size as u64);
-        transcript.append_commitent(&self.t_comm);
-        transcript.append_commitent(&self.f_comm);
-        transcript.append_commitent(&self.h1_comm);
-        transcript.append_commitent(&self.h2_comm);
-        let _beta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"beta");
-        let _gamma = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"gamma");
-        transcript.append_commitent(&self.z_comm);
+        match table_vk.domain() {
+            Some(domain) if table_vk.pins(&self.t_comm) => self.accumulate_with_transcript(
+                vk,
+                Transcript::new(b"plookup"),
+                &domain,
+                accumulator,
+                rng,
+            ),
+            _ => false,
+        }
+    }
 
-        let zeta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"zeta");
-        let zeta_omega = zeta.mul(&self.domain.group_gen);
-
-        transcript.append_commitent(&self.r_comm);
-        let _v = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"v");
-
-        let is_ok = kzg_comm_scheme.batch_verify(
-            &[
-                vec![self.f_comm, self.t_comm, self.h1_comm, self.r_comm],
-                vec![self.t_comm, self.h1_comm, self.h2_comm, self.z_comm],
-            ],
-            &[&self.opening_witness_zeta, &self.opening_witness_zeta_omega],
-            &[zeta, zeta_omega],
+    /// Same as `accumulate`, but resumes from `transcript` over `domain`.
+    pub(crate) fn accumulate_with_transcript<
+        P: Pairing<G1 = G, ScalarField = G::ScalarField>,
+        R: Rng,
+    >(
+        &self,
+        vk: &VerifierKey<P>,
+        transcript: Transcript,
+        domain: &Radix2EvaluationDomain<G::ScalarField>,
+        accumulator: &mut PairingAccumulator<G>,
+        rng: &mut R,
+    ) -> bool {
+        let (mut transcript, comms, points, evaluations) =
+            self.replay_transcript(&vk.g1, transcript, domain);
+        vk.multi_accumulate(
+            &comms,
+            &points,
+            &evaluations,
+            &self.opening_witness,
+            &mut transcript,
+            accumulator,
             rng,
+        )
+        .is_ok()
+    }
+
+    /// Replays the transcript of the prover up to the opening, and returns the opened
+    /// commitments together with their opening points and claimed evaluations.
+    ///
+    /// The commitment of r(X) is rebuilt from the other commitments, and is opened to 0 at ζ,
+    /// so the opening only holds if the quotient identity holds at ζ.
+    #[allow(clippy::type_complexity)]
+    fn replay_transcript(
+        &self,
+        g1: &G,
+        mut transcript: Transcript,
+        domain: &Radix2EvaluationDomain<G::ScalarField>,
+    ) -> (
+        Transcript,
+        Vec<G>,
+        Vec<Vec<G::ScalarField>>,
+        Vec<Vec<G::ScalarField>>,
+    ) {
+        transcript.append_u64(b"size", domain.size);
+        transcript.append_commitment(&self.t_comm);
+        transcript.append_commitment(&self.f_comm);
+        transcript.append_commitment(&self.h1_comm);
+        transcript.append_commitment(&self.h2_comm);
+        let beta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"beta");
+        let gamma = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"gamma");
+        transcript.append_commitment(&self.z_comm);
+
+        let alpha = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"alpha");
+        transcript.append_commitment(&self.q_comm);
+
+        let zeta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"zeta");
+        let zeta_omega = zeta.mul(&domain.group_gen);
+
+        let coefficients = compute_linearization_coefficients(
+            &self.evaluations,
+            &zeta,
+            &alpha,
+            &beta,
+            &gamma,
+            domain,
         );
+        let r_comm = self.z_comm.mul(coefficients.z)
+            + self.h1_comm.mul(coefficients.h1)
+            + self.h2_comm.mul(coefficients.h2)
+            + self.q_comm.mul(coefficients.quotient)
+            + g1.mul(coefficients.constant);
+
+        let comms = vec![
+            self.f_comm,
+            self.t_comm,
+            self.h1_comm,
+            self.h2_comm,
+            self.z_comm,
+            r_comm,
+        ];
+        let points = vec![
+            vec![zeta],
+            vec![zeta, zeta_omega],
+            vec![zeta, zeta_omega],
+            vec![zeta_omega],
+            vec![zeta_omega],
+            vec![zeta],
+        ];
+        let evals = &self.evaluations;
+        let evaluations = vec![
+            vec![evals.f_eval_zeta],
+            vec![evals.t_eval_zeta, evals.t_eval_zeta_omega],
+            vec![evals.h1_eval_zeta, evals.h1_eval_zeta_omega],
+            vec![evals.h2_eval_zeta_omega],
+            vec![evals.z_eval_zeta_omega],
+            vec![G::ScalarField::zero()],
+        ];
+
+        (transcript, comms, points, evaluations)
+    }
+}
+
+/// Proof of a lookup into a `MultiColumnTable`, `proof` looks the compressed witness up in the
+/// compressed table.
+pub struct MultiColumnPlookUpProof<G: CurveGroup> {
+    pub t_comms: Vec<G>,
+    pub f_comms: Vec<G>,
+    pub proof: PlookUpProof<G>,
+}
+
+impl<G: CurveGroup> MultiColumnPlookUpProof<G> {
+    /// `table_vks` pins every column of the table, over the same domain.
+    pub fn verify<P: Pairing<G1 = G, ScalarField = G::ScalarField>>(
+        &self,
+        vk: &VerifierKey<P>,
+        table_vks: &[TableVerifierKey<G>],
+    ) -> bool {
+        match self.replay_transcript(table_vks) {
+            Some((transcript, domain)) => {
+                self.proof.verify_with_transcript(vk, transcript, &domain)
+            }
+            None => false,
+        }
+    }
+
+    /// Same as `verify`, but the pairing check is deferred to `accumulator`.
+    pub fn accumulate<P: Pairing<G1 = G, ScalarField = G::ScalarField>, R: Rng>(
+        &self,
+        vk: &VerifierKey<P>,
+        table_vks: &[TableVerifierKey<G>],
+        accumulator: &mut PairingAccumulator<G>,
+        rng: &mut R,
+    ) -> bool {
+        match self.replay_transcript(table_vks) {
+            Some((transcript, domain)) => {
+                self.proof
+                    .accumulate_with_transcript(vk, transcript, &domain, accumulator, rng)
+            }
+            None => false,
+        }
+    }
+
+    /// Checks the columns against `table_vks`, derives the challenge θ compressing them, and
+    /// checks that `proof` is about the compressed commitments. Returns the transcript with
+    /// the domain of the columns.
+    #[allow(clippy::type_complexity)]
+    fn replay_transcript(
+        &self,
+        table_vks: &[TableVerifierKey<G>],
+    ) -> Option<(Transcript, Radix2EvaluationDomain<G::ScalarField>)> {
+        if self.t_comms.is_empty()
+            || self.t_comms.len() != self.f_comms.len()
+            || self.t_comms.len() != table_vks.len()
+            || !table_vks
+                .iter()
+                .all(|table_vk| table_vk.domain_size == table_vks[0].domain_size)
+            || !table_vks
+                .iter()
+                .zip(self.t_comms.iter())
+                .all(|(table_vk, t_comm)| table_vk.pins(t_comm))
+        {
+            return None;
+        }
+        let domain = table_vks[0].domain()?;
+
+        let mut transcript = Transcript::new(b"plookup");
+        for comm in self.t_comms.iter().chain(self.f_comms.iter()) {
+            transcript.append_commitment(comm);
+        }
+        let theta = <Transcript as GlobalTranscript<G>>::get_challenge(&mut transcript, b"theta");
+
+        if compress_commitments(&self.t_comms, &theta) != self.proof.t_comm
+            || compress_commitments(&self.f_comms, &theta) != self.proof.f_comm
+        {
+            return None;
+        }
 
-        is_ok
+        Some((transcript, domain))
     }
 }
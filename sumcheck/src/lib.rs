@@ -13,6 +13,7 @@ use ark_std::{
 use boolean_hypercube::BooleanHypercube;
 
 pub mod boolean_hypercube;
+pub mod multilinear;
 pub mod prover;
 pub mod verifier;
 
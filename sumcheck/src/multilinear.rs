@@ -0,0 +1,304 @@
+//! Sums of products of multilinear extensions, given by their evaluations over the boolean
+//! hypercube in the order of `BooleanHypercube`: the first variable is the most significant
+//! bit of the index.
+
+use ark_ff::{Field, Zero};
+use ark_poly::{
+    univariate::{self, DensePolynomial},
+    DenseUVPolynomial,
+};
+use ark_std::{
+    ops::{Add, Neg},
+    vec::Vec,
+};
+
+use crate::SumCheck;
+
+/// Evaluations of eq(z, x) = \prod (z_i * x_i + (1 - z_i) * (1 - x_i)) over the hypercube.
+pub fn eq_evaluations<F: Field>(z: &[F]) -> Vec<F> {
+    let mut evals = vec![F::one()];
+    for z_i in z {
+        evals = evals
+            .iter()
+            .flat_map(|e| [*e - *e * z_i, *e * z_i])
+            .collect();
+    }
+    evals
+}
+
+/// Fixes the first variable of the multilinear extension of `evals` at `r`.
+fn fix_first_variable<F: Field>(evals: &[F], r: &F) -> Vec<F> {
+    let (low, high) = evals.split_at(evals.len() / 2);
+    low.iter()
+        .zip(high.iter())
+        .map(|(l, h)| *l + (*h - l) * r)
+        .collect()
+}
+
+/// Evaluates the multilinear extension of `evals` at `point`.
+pub fn evaluate_mle<F: Field>(evals: &[F], point: &[F]) -> F {
+    assert_eq!(evals.len(), 1 << point.len());
+    point
+        .iter()
+        .fold(evals.to_vec(), |evals, r| fix_first_variable(&evals, r))[0]
+}
+
+/// \sum_k c_k * \prod_{i \in S_k} f_i(X), the f_i being multilinear extensions.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
+pub struct VirtualPolynomial<F: Field> {
+    pub num_vars: usize,
+    /// Evaluations of every f_i over the hypercube.
+    pub mles: Vec<Vec<F>>,
+    /// (c_k, S_k) for every term, S_k indexes `mles`.
+    pub terms: Vec<(F, Vec<usize>)>,
+}
+
+impl<F: Field> VirtualPolynomial<F> {
+    pub fn new(num_vars: usize, mles: Vec<Vec<F>>, terms: Vec<(F, Vec<usize>)>) -> Self {
+        assert!(mles.iter().all(|mle| mle.len() == 1 << num_vars));
+        assert!(terms
+            .iter()
+            .all(|(_, factors)| factors.iter().all(|i| *i < mles.len())));
+
+        VirtualPolynomial {
+            num_vars,
+            mles,
+            terms,
+        }
+    }
+
+    /// The degree in each variable.
+    pub fn degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, factors)| factors.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn evaluate_terms(&self, mle_evals: &[F]) -> F {
+        self.terms
+            .iter()
+            .map(|(coeff, factors)| factors.iter().fold(*coeff, |acc, i| acc * mle_evals[*i]))
+            .sum()
+    }
+}
+
+impl<F: Field> Add for VirtualPolynomial<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        if self.terms.is_empty() {
+            return rhs;
+        }
+        if rhs.terms.is_empty() {
+            return self;
+        }
+        assert_eq!(self.num_vars, rhs.num_vars);
+
+        let shift = self.mles.len();
+        let mut mles = self.mles;
+        mles.extend(rhs.mles);
+        let mut terms = self.terms;
+        terms.extend(
+            rhs.terms
+                .into_iter()
+                .map(|(coeff, factors)| (coeff, factors.into_iter().map(|i| i + shift).collect())),
+        );
+
+        VirtualPolynomial {
+            num_vars: self.num_vars,
+            mles,
+            terms,
+        }
+    }
+}
+
+impl<F: Field> Neg for VirtualPolynomial<F> {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        for (coeff, _) in self.terms.iter_mut() {
+            *coeff = -*coeff;
+        }
+        self
+    }
+}
+
+impl<F: Field> Zero for VirtualPolynomial<F> {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.terms.iter().all(|(coeff, _)| coeff.is_zero())
+    }
+}
+
+impl<F: Field> SumCheck<F> for VirtualPolynomial<F> {
+    fn num_round(&self) -> usize {
+        self.num_vars
+    }
+
+    fn evaluate(&self, point: &[F]) -> F {
+        let mle_evals = self
+            .mles
+            .iter()
+            .map(|mle| evaluate_mle(mle, point))
+            .collect::<Vec<_>>();
+        self.evaluate_terms(&mle_evals)
+    }
+
+    fn fix_variables(&self, partial_point: &[F]) -> Self {
+        let mles = self
+            .mles
+            .iter()
+            .map(|mle| {
+                partial_point
+                    .iter()
+                    .fold(mle.clone(), |evals, r| fix_first_variable(&evals, r))
+            })
+            .collect();
+
+        VirtualPolynomial {
+            num_vars: self.num_vars - partial_point.len(),
+            mles,
+            terms: self.terms.clone(),
+        }
+    }
+
+    fn to_evaluations(&self) -> Vec<F> {
+        (0..1 << self.num_vars)
+            .map(|b| {
+                let mle_evals = self.mles.iter().map(|mle| mle[b]).collect::<Vec<_>>();
+                self.evaluate_terms(&mle_evals)
+            })
+            .collect()
+    }
+
+    /// Evaluates the sum over the rest of the hypercube at 0, 1, ..., degree, then
+    /// interpolates it.
+    fn to_univariate(&self) -> univariate::SparsePolynomial<F> {
+        let degree = self.degree();
+        let half = 1 << (self.num_vars - 1);
+        let sums = (0..=degree as u64)
+            .map(|t| {
+                let t = F::from(t);
+                (0..half)
+                    .map(|b| {
+                        let mle_evals = self
+                            .mles
+                            .iter()
+                            .map(|mle| mle[b] + (mle[b + half] - mle[b]) * t)
+                            .collect::<Vec<_>>();
+                        self.evaluate_terms(&mle_evals)
+                    })
+                    .sum::<F>()
+            })
+            .collect::<Vec<_>>();
+
+        // Lagrange interpolation over the points 0, 1, ..., degree.
+        let mut poly = DensePolynomial::zero();
+        for (i, sum) in sums.iter().enumerate() {
+            let mut basis = DensePolynomial::from_coefficients_vec(vec![*sum]);
+            for j in (0..=degree).filter(|j| *j != i) {
+                let denominator = (F::from(i as u64) - F::from(j as u64)).inverse().unwrap();
+                basis = basis.naive_mul(&DensePolynomial::from_coefficients_vec(vec![
+                    -F::from(j as u64) * denominator,
+                    denominator,
+                ]));
+            }
+            poly = &poly + &basis;
+        }
+
+        univariate::SparsePolynomial::from_coefficients_vec(
+            poly.coeffs
+                .into_iter()
+                .enumerate()
+                .filter(|(_, coeff)| !coeff.is_zero())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{One, Zero};
+    use ark_poly::{univariate::SparsePolynomial, Polynomial};
+    use sample_field::F101;
+
+    use super::{eq_evaluations, evaluate_mle, VirtualPolynomial};
+    use crate::{prover::Prover, verifier::Verifier, SumCheck};
+
+    fn mle(evals: &[u64]) -> Vec<F101> {
+        evals.iter().map(|x| F101::from(*x)).collect()
+    }
+
+    #[test]
+    fn test_evaluate_mle() {
+        // f(x_1, x_2) = 3 + 2 * x_1 + 5 * x_1 * x_2
+        let evals = mle(&[3, 3, 5, 10]);
+        let point = [F101::from(7), F101::from(11)];
+        assert_eq!(
+            evaluate_mle(&evals, &point),
+            F101::from(3 + 2 * 7 + 5 * 7 * 11)
+        );
+
+        let eq = eq_evaluations(&point);
+        let sum = eq.iter().zip(evals.iter()).map(|(e, f)| *e * f).sum();
+        assert_eq!(evaluate_mle(&evals, &point), sum);
+        assert_eq!(eq_evaluations::<F101>(&[]), vec![F101::one()]);
+    }
+
+    #[test]
+    fn test_to_univariate() {
+        // 2 * f * g + h, whose first variable has degree 2.
+        let g = VirtualPolynomial::new(
+            2,
+            vec![
+                mle(&[1, 2, 3, 4]),
+                mle(&[5, 6, 7, 8]),
+                mle(&[9, 10, 11, 12]),
+            ],
+            vec![(F101::from(2), vec![0, 1]), (F101::one(), vec![2])],
+        );
+        let uni_poly = g.to_univariate();
+        for t in 0..4u64 {
+            let t = F101::from(t);
+            let sum = g.evaluate(&[t, F101::zero()]) + g.evaluate(&[t, F101::one()]);
+            assert_eq!(uni_poly.evaluate(&t), sum);
+        }
+        assert_eq!(
+            uni_poly,
+            SparsePolynomial::from_coefficients_vec(vec![
+                (0, F101::from(2 * (5 + 2 * 6) + 9 + 10)),
+                (1, F101::from(2 * (12 + 16) + 4)),
+                (2, F101::from(2 * 2 * 2 * 2)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sumcheck_protocol() {
+        let g = VirtualPolynomial::new(
+            3,
+            vec![
+                eq_evaluations(&[F101::from(3), F101::from(4), F101::from(5)]),
+                mle(&[1, 2, 3, 4, 5, 6, 7, 8]),
+                mle(&[8, 7, 6, 5, 4, 3, 2, 1]),
+            ],
+            vec![(F101::one(), vec![0, 1, 2])],
+        ) + -VirtualPolynomial::new(3, vec![mle(&[1; 8])], vec![(F101::one(), vec![0])]);
+
+        let sum = g.to_evaluations().iter().sum();
+        let mut prover = Prover::new(g.clone());
+        assert_eq!(prover.get_sum(), sum);
+
+        let mut verifier = Verifier::new(g.clone(), sum);
+        let mut r_i = F101::zero();
+        for j in 0..g.num_round() {
+            let prover_msg = prover.start_round(j, r_i);
+            r_i = verifier.receive_msg(j, prover_msg);
+        }
+    }
+}
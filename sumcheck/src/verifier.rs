@@ -1,12 +1,45 @@
+use ark_ec::pairing::Pairing;
 use ark_ff::{Field, Zero};
 use ark_poly::{univariate::SparsePolynomial, Polynomial};
 use ark_std::test_rng;
+use kzg::multilinear::{MultilinearKZGProof, MultilinearVerifierKey};
 
 use crate::SumCheck;
 
 pub struct Verifier<F: Field, S: SumCheck<F>> {
     /// g is the multivariate polynomial to be proved
     g: S,
+    /// checks the rounds, g is only needed for the final query
+    oracle_verifier: OracleVerifier<F>,
+}
+
+impl<F: Field, S: SumCheck<F>> Verifier<F, S> {
+    pub fn new(g: S, sum: F) -> Self {
+        Self {
+            oracle_verifier: OracleVerifier::new(g.num_round(), sum),
+            g,
+        }
+    }
+
+    pub fn receive_msg(&mut self, j: usize, g_i: SparsePolynomial<F>) -> F {
+        let mut rng = test_rng();
+        let r = F::rand(&mut rng);
+        self.oracle_verifier.receive_msg_with_challenge(j, g_i, r);
+
+        if j == self.g.num_round() - 1 {
+            // the last round, the verifier evaluates g itself.
+            let (point, eval) = self.oracle_verifier.sub_claim();
+            assert_eq!(self.g.evaluate(&point), eval);
+        }
+
+        r
+    }
+}
+
+/// The sumcheck verifier without g, which only has oracle access to it: the protocol ends
+/// with the claim g(r) = eval, answered e.g. by an opening of a commitment to g.
+pub struct OracleVerifier<F: Field> {
+    num_round: usize,
     // univariate polynolial sent from prover
     intermediate_univariate_poly: SparsePolynomial<F>,
     // random number selected by verifier during sumcheck protocol
@@ -15,19 +48,20 @@ pub struct Verifier<F: Field, S: SumCheck<F>> {
     sum: F,
 }
 
-impl<F: Field, S: SumCheck<F>> Verifier<F, S> {
-    pub fn new(g: S, sum: F) -> Self {
+impl<F: Field> OracleVerifier<F> {
+    pub fn new(num_round: usize, sum: F) -> Self {
         Self {
-            g,
+            num_round,
             sum,
             r: vec![],
             intermediate_univariate_poly: SparsePolynomial::zero(),
         }
     }
 
-    pub fn receive_msg(&mut self, j: usize, g_i: SparsePolynomial<F>) -> F {
-        let mut rng = test_rng();
-        let r = F::rand(&mut rng);
+    /// Checks g_j and records the challenge r_j, which the caller draws after g_j, e.g. from
+    /// a transcript that has absorbed it. The prover must not know r_j before sending g_j.
+    pub fn receive_msg_with_challenge(&mut self, j: usize, g_i: SparsePolynomial<F>, r: F) {
+        assert!(j == self.r.len() && j < self.num_round);
 
         let eval_0 = g_i.evaluate(&F::zero());
         let eval_1 = g_i.evaluate(&F::one());
@@ -36,29 +70,128 @@ impl<F: Field, S: SumCheck<F>> Verifier<F, S> {
         if j == 0 {
             // the first round
             assert_eq!(eval_sum, self.sum);
-        } else if 0 < j && j < self.g.num_round() - 1 {
+        } else {
             // the jth round
             let eval_r = self
                 .intermediate_univariate_poly
-                .evaluate(&self.r.last().unwrap());
-            assert_eq!(eval_sum, eval_r);
-        } else if j == self.g.num_round() - 1 {
-            // the last round
-            let eval_r = self
-                .intermediate_univariate_poly
-                .evaluate(&self.r.last().unwrap());
+                .evaluate(self.r.last().unwrap());
             assert_eq!(eval_sum, eval_r);
+        }
+
+        self.intermediate_univariate_poly = g_i;
+        self.r.push(r);
+    }
+
+    /// The point r and the value g(r) the prover claims after the last round.
+    pub fn sub_claim(&self) -> (Vec<F>, F) {
+        assert_eq!(self.r.len(), self.num_round, "The sumcheck is not over");
+        let eval = self
+            .intermediate_univariate_poly
+            .evaluate(self.r.last().unwrap());
+
+        (self.r.clone(), eval)
+    }
+
+    /// Answers the final query of a sumcheck over a multilinear g with an opening of its
+    /// commitment `g_comm` at r.
+    pub fn verify_opening<P: Pairing<ScalarField = F>>(
+        &self,
+        vk: &MultilinearVerifierKey<P>,
+        g_comm: &P::G1,
+        proof: &MultilinearKZGProof<P::G1>,
+    ) {
+        let (point, eval) = self.sub_claim();
+        assert_eq!(proof.opening_value(), eval);
+        assert!(
+            vk.verify(g_comm, &point, proof).is_ok(),
+            "The opening of g does not verify"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::{One, Zero};
+    use ark_poly::{
+        multivariate::{SparsePolynomial, SparseTerm, Term},
+        DenseMVPolynomial,
+    };
+    use ark_std::{rand::Rng, test_rng, UniformRand};
+    use kzg::multilinear::{MultilinearKZGCommitmentScheme, MultilinearSRS};
 
-            self.r.push(r.clone());
+    use super::OracleVerifier;
+    use crate::{prover::Prover, SumCheck};
 
-            let eval_full = self.g.evaluate(&self.r);
-            let eval_full_g = g_i.evaluate(&r);
-            assert_eq!(eval_full, eval_full_g);
+    /// A random multilinear polynomial, with a coefficient for every subset of the variables.
+    fn rand_multilinear<R: Rng>(num_vars: usize, rng: &mut R) -> SparsePolynomial<Fr, SparseTerm> {
+        let terms = (0..1 << num_vars)
+            .map(|mask: usize| {
+                let vars = (0..num_vars)
+                    .filter(|i| mask >> i & 1 == 1)
+                    .map(|i| (i, 1))
+                    .collect();
+                (Fr::rand(rng), SparseTerm::new(vars))
+            })
+            .collect();
+        SparsePolynomial::from_coefficients_vec(num_vars, terms)
+    }
+
+    /// Runs the sumcheck over `g` against a verifier that does not hold it, with a fresh
+    /// challenge every round.
+    fn oracle_sumcheck<R: Rng>(
+        g: &SparsePolynomial<Fr, SparseTerm>,
+        rng: &mut R,
+    ) -> OracleVerifier<Fr> {
+        let mut prover = Prover::new(g.clone());
+        let mut verifier = OracleVerifier::new(g.num_round(), prover.get_sum());
+        let mut r_i = Fr::zero();
+        for j in 0..g.num_round() {
+            let prover_msg = prover.start_round(j, r_i);
+            r_i = Fr::rand(rng);
+            verifier.receive_msg_with_challenge(j, prover_msg, r_i);
         }
+        verifier
+    }
 
-        self.intermediate_univariate_poly = g_i;
-        self.r.push(r.clone());
+    #[test]
+    fn test_sumcheck_with_kzg_opening() {
+        let num_vars = 4;
+        let mut rng = test_rng();
+        let srs = MultilinearSRS::<Bls12_381>::new(num_vars, &mut rng);
+        let (pk, vk) = srs.trim(num_vars).unwrap();
+        let scheme = MultilinearKZGCommitmentScheme(&pk);
 
-        r
+        let g = rand_multilinear(num_vars, &mut rng);
+        let evals = g.to_evaluations();
+        let g_comm = scheme.commit(&evals).unwrap();
+
+        let verifier = oracle_sumcheck(&g, &mut rng);
+        let (point, eval) = verifier.sub_claim();
+        assert_eq!(SumCheck::evaluate(&g, &point), eval);
+        let proof = scheme.prove(&evals, &point).unwrap();
+        verifier.verify_opening(&vk, &g_comm, &proof);
+    }
+
+    #[test]
+    #[should_panic(expected = "The opening of g does not verify")]
+    fn test_sumcheck_rejects_opening_against_another_commitment() {
+        let num_vars = 3;
+        let mut rng = test_rng();
+        let srs = MultilinearSRS::<Bls12_381>::new(num_vars, &mut rng);
+        let (pk, vk) = srs.trim(num_vars).unwrap();
+        let scheme = MultilinearKZGCommitmentScheme(&pk);
+
+        let g = rand_multilinear(num_vars, &mut rng);
+        let evals = g.to_evaluations();
+        let mut other = evals.clone();
+        other[0] += Fr::one();
+        let g_comm = scheme.commit(&other).unwrap();
+
+        // the sumcheck and the opening agree with each other, but not with the commitment.
+        let verifier = oracle_sumcheck(&g, &mut rng);
+        let (point, _) = verifier.sub_claim();
+        let proof = scheme.prove(&evals, &point).unwrap();
+        verifier.verify_opening(&vk, &g_comm, &proof);
     }
 }